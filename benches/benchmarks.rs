@@ -1,10 +1,20 @@
-use std::sync::Mutex;
+mod boxed_bvh;
 
+use std::sync::{Arc, Mutex};
+
+use boxed_bvh::BoxedBvh;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use glam::Vec3;
 use lazy_static::lazy_static;
 use rl_ball_sym::{
     load_dropshot, load_hoops, load_soccar, load_soccar_throwback,
-    simulation::{ball::Ball, game::Game},
+    simulation::{
+        ball::{Ball, BallSolver},
+        bvh::{Bvh, BvhBuilder},
+        game::Game,
+        geometry::{Ray, Sphere},
+        prediction::FixedBallPrediction,
+    },
 };
 
 lazy_static! {
@@ -34,8 +44,6 @@ fn load_dropshot_benchmark(c: &mut Criterion) {
     c.bench_function("load_dropshot", |b| b.iter(load_dropshot));
 }
 
-// Disabled for now. This test spams the console with warnings and makes the benchmark output difficult to read
-#[allow(unused)]
 fn load_soccar_throwback_benchmark(c: &mut Criterion) {
     c.bench_function("load_soccar_throwback", |b| b.iter(load_soccar_throwback));
 }
@@ -52,6 +60,34 @@ fn get_ball_prediction_struct_benchmark(c: &mut Criterion) {
     c.bench_function("get_ball_prediction/soccar", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut game))));
 }
 
+fn get_ball_prediction_struct_sah_benchmark(c: &mut Criterion) {
+    let mut game = load_soccar();
+    Arc::make_mut(&mut game.arena).collision_mesh = game.arena.collision_mesh.rebuild(BvhBuilder::Sah);
+
+    c.bench_function("get_ball_prediction/soccar_sah", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut game))));
+}
+
+fn get_ball_prediction_struct_continuous_benchmark(c: &mut Criterion) {
+    let mut game = load_soccar();
+    game.continuous_collision = true;
+
+    c.bench_function("get_ball_prediction/soccar_continuous", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut game))));
+}
+
+fn get_ball_prediction_struct_bullet_benchmark(c: &mut Criterion) {
+    let mut game = load_soccar();
+    game.ball_solver = BallSolver::Bullet;
+
+    c.bench_function("get_ball_prediction/soccar_bullet", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut game))));
+}
+
+fn fixed_ball_prediction_benchmark(c: &mut Criterion) {
+    let mut game = load_soccar();
+    let mut prediction = Box::new(FixedBallPrediction::<720>::default());
+
+    c.bench_function("get_ball_prediction/soccar_fixed", |b| b.iter(|| prediction.fill(black_box(&mut game))));
+}
+
 fn get_ball_prediction_struct_hoops_benchmark(c: &mut Criterion) {
     let mut game = load_hoops();
 
@@ -70,13 +106,147 @@ fn get_ball_prediction_struct_throwback(c: &mut Criterion) {
     c.bench_function("get_ball_prediction/throwback", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut game))));
 }
 
+// spheres on the floor, against a wall, in a corner, in a goal and in the air
+fn bvh_queries() -> Vec<Sphere> {
+    [Vec3::new(0., 0., 93.), Vec3::new(4000., 0., 1000.), Vec3::new(3900., 5000., 100.), Vec3::new(0., 5500., 300.), Vec3::new(1000., 1000., 1000.)]
+        .iter()
+        .map(|&center| Sphere {
+            center,
+            radius: 93.15,
+        })
+        .collect()
+}
+
+fn count_intersecting(bvh: &Bvh, query: &Sphere) -> usize {
+    let mut count = 0;
+    bvh.for_each_intersecting(query, |_| count += 1);
+    count
+}
+
+fn bvh_intersect_benchmark(c: &mut Criterion) {
+    let game = load_soccar();
+    let queries = bvh_queries();
+
+    c.bench_function("bvh/intersect", |b| {
+        b.iter(|| {
+            for query in &queries {
+                black_box(count_intersecting(&game.arena.collision_mesh, black_box(query)));
+            }
+        })
+    });
+}
+
+// the flat layout against the boxed one that it replaced, on the same triangles and queries
+// when the flat layout went in, the two measured the same within noise (about 2.0 us for these queries), it was a layout change, not a speedup
+fn bvh_layout_benchmark(c: &mut Criterion) {
+    let game = load_soccar();
+    let boxed = BoxedBvh::from(&game.arena.collision_mesh.primitives);
+    let queries = bvh_queries();
+
+    for query in &queries {
+        assert_eq!(boxed.intersect(query).len(), count_intersecting(&game.arena.collision_mesh, query));
+    }
+
+    let mut group = c.benchmark_group("bvh/layout");
+
+    group.bench_function("flat", |b| {
+        b.iter(|| {
+            for query in &queries {
+                black_box(count_intersecting(&game.arena.collision_mesh, black_box(query)));
+            }
+        })
+    });
+
+    group.bench_function("boxed", |b| {
+        b.iter(|| {
+            for query in &queries {
+                black_box(boxed.intersect(black_box(query)));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn bvh_build_benchmark(c: &mut Criterion) {
+    let game = load_soccar();
+
+    c.bench_function("bvh/build_morton", |b| b.iter(|| game.arena.collision_mesh.rebuild(BvhBuilder::Morton)));
+    c.bench_function("bvh/build_sah", |b| b.iter(|| game.arena.collision_mesh.rebuild(BvhBuilder::Sah)));
+}
+
+fn bvh_collide_benchmark(c: &mut Criterion) {
+    let game = load_soccar();
+    let queries = bvh_queries();
+
+    c.bench_function("bvh/collide", |b| {
+        b.iter(|| {
+            for query in &queries {
+                black_box(game.arena.collision_mesh.collide(black_box(query)));
+            }
+        })
+    });
+}
+
+fn bvh_raycast_benchmark(c: &mut Criterion) {
+    let game = load_soccar();
+
+    // from the middle of the field to the same places as bvh_queries, and long rays across the field
+    let rays: Vec<Ray> = bvh_queries()
+        .iter()
+        .map(|query| Ray {
+            start: Vec3::new(0., 0., 500.),
+            direction: (query.center - Vec3::new(0., 0., 500.)) * 2.,
+        })
+        .chain([Ray {
+            start: Vec3::new(-3000., -4000., 100.),
+            direction: Vec3::new(6000., 8000., 1000.),
+        }])
+        .collect();
+
+    c.bench_function("bvh/raycast", |b| {
+        b.iter(|| {
+            for ray in &rays {
+                black_box(game.arena.collision_mesh.raycast(black_box(ray)));
+            }
+        })
+    });
+
+    c.bench_function("bvh/raycast_any", |b| {
+        b.iter(|| {
+            for ray in &rays {
+                black_box(game.arena.collision_mesh.raycast_any(black_box(ray)));
+            }
+        })
+    });
+}
+
+fn bvh_signed_distance_benchmark(c: &mut Criterion) {
+    let game = load_soccar();
+    let queries = bvh_queries();
+
+    // the distance to the nearest wall for every car on the field
+    c.bench_function("bvh/signed_distance", |b| {
+        b.iter(|| {
+            for query in &queries {
+                black_box(game.arena.collision_mesh.signed_distance(black_box(query.center), 500.));
+            }
+        })
+    });
+}
+
+criterion_group!(init, init_benchmark, load_soccar_benchmark, load_hoops_benchmark, load_dropshot_benchmark, load_soccar_throwback_benchmark,);
 criterion_group!(
-    init,
-    init_benchmark,
-    load_soccar_benchmark,
-    load_hoops_benchmark,
-    load_dropshot_benchmark,
-    /* load_soccar_throwback_benchmark, */
+    prediction,
+    get_ball_prediction_struct_with_time_benchmark,
+    get_ball_prediction_struct_benchmark,
+    get_ball_prediction_struct_sah_benchmark,
+    get_ball_prediction_struct_continuous_benchmark,
+    get_ball_prediction_struct_bullet_benchmark,
+    fixed_ball_prediction_benchmark,
+    get_ball_prediction_struct_hoops_benchmark,
+    get_ball_prediction_struct_dropshot,
+    get_ball_prediction_struct_throwback
 );
-criterion_group!(prediction, get_ball_prediction_struct_with_time_benchmark, get_ball_prediction_struct_benchmark, get_ball_prediction_struct_hoops_benchmark, get_ball_prediction_struct_dropshot, get_ball_prediction_struct_throwback);
-criterion_main!(init, prediction);
+criterion_group!(bvh, bvh_build_benchmark, bvh_intersect_benchmark, bvh_layout_benchmark, bvh_collide_benchmark, bvh_raycast_benchmark, bvh_signed_distance_benchmark);
+criterion_main!(init, prediction, bvh);
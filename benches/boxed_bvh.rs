@@ -0,0 +1,134 @@
+// The BVH as it was before it was flattened into one array of nodes, kept only so that the benches have a baseline
+// Every node is its own allocation, and the traversal stack is allocated on every query
+
+use rl_ball_sym::simulation::{
+    geometry::{Aabb, Sphere, Tri},
+    morton::Morton,
+};
+
+#[derive(Clone)]
+pub struct BoxedBvhNode {
+    pub box_: Aabb,
+    pub right: Option<Box<BoxedBvhNode>>,
+    pub left: Option<Box<BoxedBvhNode>>,
+    pub primitive: Option<Tri>,
+    pub morton: Option<u64>,
+}
+
+impl BoxedBvhNode {
+    fn branch(right: Box<BoxedBvhNode>, left: Box<BoxedBvhNode>) -> Box<Self> {
+        Box::new(Self {
+            box_: right.box_.add(&left.box_),
+            right: Some(right),
+            left: Some(left),
+            primitive: None,
+            morton: None,
+        })
+    }
+
+    fn leaf(primitive: Tri, box_: Aabb, morton_code: u64) -> Box<Self> {
+        Box::new(Self {
+            box_,
+            right: None,
+            left: None,
+            primitive: Some(primitive),
+            morton: Some(morton_code),
+        })
+    }
+}
+
+pub struct BoxedBvh {
+    pub root: Box<BoxedBvhNode>,
+}
+
+impl BoxedBvh {
+    pub fn from(primitives: &[Tri]) -> Self {
+        let boxes: Vec<Aabb> = primitives.iter().map(Aabb::from_tri).collect();
+
+        let mut global_box = boxes[0];
+        for b in &boxes {
+            global_box = global_box.add(b);
+        }
+
+        let morton = Morton::from(&global_box);
+        let mut sorted_leaves: Vec<Box<BoxedBvhNode>> = boxes.iter().zip(primitives).map(|(box_, primitive)| BoxedBvhNode::leaf(primitive.clone(), *box_, morton.get_code(box_))).collect();
+        sorted_leaves.sort_unstable_by_key(|leaf| leaf.morton);
+
+        Self {
+            root: BoxedBvh::generate_hierarchy(&sorted_leaves, 0, primitives.len() - 1),
+        }
+    }
+
+    fn generate_hierarchy(sorted_leaves: &[Box<BoxedBvhNode>], first: usize, last: usize) -> Box<BoxedBvhNode> {
+        if first == last {
+            return sorted_leaves[first].clone();
+        }
+
+        let split = first + ((last - first) / 2);
+
+        let right = BoxedBvh::generate_hierarchy(sorted_leaves, first, split);
+        let left = BoxedBvh::generate_hierarchy(sorted_leaves, split + 1, last);
+
+        BoxedBvhNode::branch(right, left)
+    }
+
+    pub fn intersect(&self, query_object: &Sphere) -> Vec<Tri> {
+        let query_box: Aabb = Aabb::from_sphere(query_object);
+
+        let mut hits = Vec::with_capacity(16);
+        let mut stack: Vec<&BoxedBvhNode> = Vec::with_capacity(32);
+
+        let mut node = &*self.root;
+        loop {
+            let right_og = node.right.as_deref();
+
+            let mut traverse_left = false;
+            if let Some(left) = node.left.as_deref() {
+                if left.box_.intersect_self(&query_box) {
+                    match &left.primitive {
+                        Some(left_tri) => {
+                            if left_tri.intersect_sphere(query_object) {
+                                hits.push(left_tri.clone());
+                            }
+                        }
+                        None => {
+                            traverse_left = true;
+                            node = left;
+                        }
+                    }
+                }
+            }
+
+            let mut traverse_right = false;
+            if let Some(right) = right_og {
+                if right.box_.intersect_self(&query_box) {
+                    match &right.primitive {
+                        Some(right_tri) => {
+                            if right_tri.intersect_sphere(query_object) {
+                                hits.push(right_tri.clone());
+                            }
+                        }
+                        None => {
+                            traverse_right = true;
+
+                            if traverse_left {
+                                stack.push(right);
+                            } else {
+                                node = right;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !(traverse_left || traverse_right) {
+                match stack.pop() {
+                    Some(n) => node = n,
+                    None => break,
+                }
+            }
+        }
+
+        hits
+    }
+}
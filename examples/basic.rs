@@ -1,7 +1,7 @@
+use glam::Vec3;
 use rl_ball_sym::load_soccar;
 use rl_ball_sym::simulation::ball::{Ball, BallPrediction};
 use rl_ball_sym::simulation::game::Game;
-use vvec3::Vec3;
 
 use rand::Rng;
 
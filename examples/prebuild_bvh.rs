@@ -0,0 +1,17 @@
+use rl_ball_sym::simulation::bvh::BvhBuilder;
+use rl_ball_sym::simulation::game::Game;
+use rl_ball_sym::{load_dropshot, load_hoops, load_soccar, load_soccar_throwback};
+
+// Regenerates the trees in assets/prebuilt, run it without the prebuilt-bvh feature:
+// cargo run --release --example prebuild_bvh
+pub fn main() {
+    let arenas = [("soccar", load_soccar as fn() -> Game), ("hoops", load_hoops), ("dropshot", load_dropshot), ("throwback", load_soccar_throwback)];
+
+    for (name, load) in arenas {
+        let bvh = load().arena.collision_mesh.rebuild(BvhBuilder::Sah);
+
+        let path = format!("assets/prebuilt/{}.bvh", name);
+        bvh.to_bin_file(&path).unwrap();
+        println!("Wrote {} ({} triangles)", path, bvh.num_leaves);
+    }
+}
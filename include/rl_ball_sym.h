@@ -0,0 +1,141 @@
+#ifndef RL_BALL_SYM_H
+#define RL_BALL_SYM_H
+
+/* Generated by cbindgen from src/capi.rs, don't edit this file by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define RLBS_RLBOT_MAX_SLICES 360
+
+typedef struct RlbsGame RlbsGame;
+
+typedef struct RlbsVector3 {
+  float x;
+  float y;
+  float z;
+} RlbsVector3;
+
+typedef struct RlbsRotator {
+  float pitch;
+  float yaw;
+  float roll;
+} RlbsRotator;
+
+typedef struct RlbsPhysics {
+  struct RlbsVector3 location;
+  struct RlbsRotator rotation;
+  struct RlbsVector3 velocity;
+  struct RlbsVector3 angular_velocity;
+} RlbsPhysics;
+
+typedef struct RlbsPredictionSlice {
+  struct RlbsPhysics physics;
+  float game_seconds;
+} RlbsPredictionSlice;
+
+typedef struct RlbsFixedBallPrediction {
+  struct RlbsPredictionSlice slices[RLBS_RLBOT_MAX_SLICES];
+  int32_t num_slices;
+} RlbsFixedBallPrediction;
+
+typedef struct RlbsFixedBallPrediction RlbsRLBotBallPrediction;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Loads the standard soccar arena, free it with `rlbs_free_game`
+ */
+struct RlbsGame *rlbs_load_soccar(void);
+
+/**
+ * Loads the soccar arena with a heatseeker ball, free it with `rlbs_free_game`
+ */
+struct RlbsGame *rlbs_load_heatseeker(void);
+
+/**
+ * Loads the hoops arena, free it with `rlbs_free_game`
+ */
+struct RlbsGame *rlbs_load_hoops(void);
+
+/**
+ * Loads the dropshot arena, free it with `rlbs_free_game`
+ */
+struct RlbsGame *rlbs_load_dropshot(void);
+
+/**
+ * Loads the throwback stadium, free it with `rlbs_free_game`
+ */
+struct RlbsGame *rlbs_load_soccar_throwback(void);
+
+/**
+ * Frees a game from one of the `rlbs_load_*` functions, null is ignored
+ *
+ * # Safety
+ *
+ * `game` must be null or from one of the `rlbs_load_*` functions, and it can't be used again
+ */
+void rlbs_free_game(struct RlbsGame *game);
+
+/**
+ * Sets the ball's state, `time` is the game's time in seconds
+ *
+ * # Safety
+ *
+ * `game` must be null or a live game from one of the `rlbs_load_*` functions
+ */
+void rlbs_set_ball(struct RlbsGame *game,
+                   float time,
+                   struct RlbsVector3 location,
+                   struct RlbsVector3 velocity,
+                   struct RlbsVector3 angular_velocity);
+
+/**
+ * Gets the ball's state, a null game gives a zeroed slice
+ *
+ * # Safety
+ *
+ * `game` must be null or a live game from one of the `rlbs_load_*` functions
+ */
+struct RlbsPredictionSlice rlbs_get_ball(const struct RlbsGame *game);
+
+/**
+ * Predicts the ball's path into the caller's `slices`, `steps_per_slice` steps of 1/120 of a second apart
+ * The ball's state is left as it was, returns the number of slices that were written
+ *
+ * # Safety
+ *
+ * `game` must be null or a live game from one of the `rlbs_load_*` functions, that no other thread is using
+ * `slices` must be null or point to `num_slices` slices
+ */
+uintptr_t rlbs_predict(struct RlbsGame *game,
+                       struct RlbsPredictionSlice *slices,
+                       uintptr_t num_slices,
+                       uintptr_t steps_per_slice);
+
+/**
+ * Fills RLBot's ball prediction, 6 seconds of slices that are 1/60 of a second apart
+ * The ball's state is left as it was
+ *
+ * # Safety
+ *
+ * `game` must be null or a live game from one of the `rlbs_load_*` functions, that no other thread is using
+ * `prediction` must be null or point to a `RlbsRLBotBallPrediction`
+ */
+void rlbs_predict_rlbot(struct RlbsGame *game,
+                        RlbsRLBotBallPrediction *prediction);
+
+/**
+ * The number of slices in a `RlbsRLBotBallPrediction`
+ */
+uintptr_t rlbs_rlbot_max_slices(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* RL_BALL_SYM_H */
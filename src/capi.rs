@@ -0,0 +1,157 @@
+// The C ABI, enabled by the `cdylib` feature
+// include/rl_ball_sym.h is generated from this file with cbindgen, see the README
+// These use doc comments so that cbindgen copies them into the header
+
+use crate::simulation::game::Game;
+use crate::simulation::prediction::{fill_slices, PredictionSlice, RLBotBallPrediction, Vector3, RLBOT_MAX_SLICES};
+use crate::{load_dropshot, load_heatseeker, load_hoops, load_soccar, load_soccar_throwback};
+use std::slice;
+
+/// Loads the standard soccar arena, free it with `rlbs_free_game`
+#[no_mangle]
+pub extern "C" fn rlbs_load_soccar() -> *mut Game {
+    Box::into_raw(Box::new(load_soccar()))
+}
+
+/// Loads the soccar arena with a heatseeker ball, free it with `rlbs_free_game`
+#[no_mangle]
+pub extern "C" fn rlbs_load_heatseeker() -> *mut Game {
+    Box::into_raw(Box::new(load_heatseeker()))
+}
+
+/// Loads the hoops arena, free it with `rlbs_free_game`
+#[no_mangle]
+pub extern "C" fn rlbs_load_hoops() -> *mut Game {
+    Box::into_raw(Box::new(load_hoops()))
+}
+
+/// Loads the dropshot arena, free it with `rlbs_free_game`
+#[no_mangle]
+pub extern "C" fn rlbs_load_dropshot() -> *mut Game {
+    Box::into_raw(Box::new(load_dropshot()))
+}
+
+/// Loads the throwback stadium, free it with `rlbs_free_game`
+#[no_mangle]
+pub extern "C" fn rlbs_load_soccar_throwback() -> *mut Game {
+    Box::into_raw(Box::new(load_soccar_throwback()))
+}
+
+/// Frees a game from one of the `rlbs_load_*` functions, null is ignored
+///
+/// # Safety
+///
+/// `game` must be null or from one of the `rlbs_load_*` functions, and it can't be used again
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_free_game(game: *mut Game) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// Sets the ball's state, `time` is the game's time in seconds
+///
+/// # Safety
+///
+/// `game` must be null or a live game from one of the `rlbs_load_*` functions
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_set_ball(game: *mut Game, time: f32, location: Vector3, velocity: Vector3, angular_velocity: Vector3) {
+    if let Some(game) = game.as_mut() {
+        game.ball.update(time, location.into(), velocity.into(), angular_velocity.into());
+    }
+}
+
+/// Gets the ball's state, a null game gives a zeroed slice
+///
+/// # Safety
+///
+/// `game` must be null or a live game from one of the `rlbs_load_*` functions
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_get_ball(game: *const Game) -> PredictionSlice {
+    game.as_ref().map(|game| PredictionSlice::from(&game.ball)).unwrap_or_default()
+}
+
+/// Predicts the ball's path into the caller's `slices`, `steps_per_slice` steps of 1/120 of a second apart
+/// The ball's state is left as it was, returns the number of slices that were written
+///
+/// # Safety
+///
+/// `game` must be null or a live game from one of the `rlbs_load_*` functions, that no other thread is using
+/// `slices` must be null or point to `num_slices` slices
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_predict(game: *mut Game, slices: *mut PredictionSlice, num_slices: usize, steps_per_slice: usize) -> usize {
+    let Some(game) = game.as_mut() else {
+        return 0;
+    };
+
+    if slices.is_null() {
+        return 0;
+    }
+
+    fill_slices(game, slice::from_raw_parts_mut(slices, num_slices), steps_per_slice);
+
+    num_slices
+}
+
+/// Fills RLBot's ball prediction, 6 seconds of slices that are 1/60 of a second apart
+/// The ball's state is left as it was
+///
+/// # Safety
+///
+/// `game` must be null or a live game from one of the `rlbs_load_*` functions, that no other thread is using
+/// `prediction` must be null or point to a `RlbsRLBotBallPrediction`
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_predict_rlbot(game: *mut Game, prediction: *mut RLBotBallPrediction) {
+    if let (Some(game), Some(prediction)) = (game.as_mut(), prediction.as_mut()) {
+        prediction.fill_every(game, 2);
+    }
+}
+
+/// The number of slices in a `RlbsRLBotBallPrediction`
+#[no_mangle]
+pub extern "C" fn rlbs_rlbot_max_slices() -> usize {
+    RLBOT_MAX_SLICES
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::simulation::prediction::FixedBallPrediction;
+    use std::ptr;
+
+    #[test]
+    fn predict() {
+        let game = rlbs_load_soccar();
+
+        unsafe {
+            let location = Vector3 {
+                x: 0.,
+                y: 0.,
+                z: 1000.,
+            };
+            rlbs_set_ball(game, 5., location, Vector3::default(), Vector3::default());
+            assert_eq!(rlbs_get_ball(game).physics.location, location);
+
+            let mut slices = vec![PredictionSlice::default(); 240];
+            assert_eq!(rlbs_predict(game, slices.as_mut_ptr(), slices.len(), 1), 240);
+            assert!((slices[239].game_seconds - 7.).abs() < 0.001);
+            assert!(slices[239].physics.location.z < 1000.);
+
+            // the ball is put back where it was
+            assert_eq!(rlbs_get_ball(game).physics.location, location);
+
+            let mut prediction = Box::new(FixedBallPrediction::default());
+            rlbs_predict_rlbot(game, &mut *prediction);
+            assert_eq!(prediction.num_slices as usize, rlbs_rlbot_max_slices());
+            assert_eq!(prediction.slices[119], slices[239]);
+
+            // null pointers are ignored
+            assert_eq!(rlbs_predict(ptr::null_mut(), slices.as_mut_ptr(), slices.len(), 1), 0);
+            assert_eq!(rlbs_predict(game, ptr::null_mut(), 10, 1), 0);
+            assert_eq!(rlbs_get_ball(ptr::null()), PredictionSlice::default());
+            rlbs_free_game(ptr::null_mut());
+
+            rlbs_free_game(game);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io(io::Error),
+    // the file ended in the middle of a number, a vertex or a triangle
+    Truncated {
+        file: &'static str,
+        len: usize,
+    },
+    // the number of ids isn't a multiple of 3, so the last triangle is missing a vertex
+    IncompleteTriangle {
+        ids: usize,
+    },
+    // a triangle refers to a vertex that doesn't exist
+    IndexOutOfBounds {
+        triangle: usize,
+        index: i32,
+        num_vertices: usize,
+    },
+    // a triangle has no area, so it doesn't have a normal
+    DegenerateTriangle {
+        triangle: usize,
+    },
+    // a vertex has a coordinate that is NaN or infinite
+    NanVertex {
+        vertex: usize,
+    },
+    // a line of an OBJ file couldn't be understood
+    Parse {
+        line: usize,
+        message: String,
+    },
+    // the mesh has no triangles, so no collision mesh can be built from it
+    Empty,
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io(error) => write!(f, "problem reading mesh: {}", error),
+            MeshError::Truncated {
+                file,
+                len,
+            } => write!(f, "{} is truncated ({} bytes)", file, len),
+            MeshError::IncompleteTriangle {
+                ids,
+            } => write!(f, "{} ids don't make a whole number of triangles", ids),
+            MeshError::IndexOutOfBounds {
+                triangle,
+                index,
+                num_vertices,
+            } => write!(f, "triangle {} refers to vertex {}, but there are only {} vertices", triangle, index, num_vertices),
+            MeshError::DegenerateTriangle {
+                triangle,
+            } => write!(f, "triangle {} has no area", triangle),
+            MeshError::NanVertex {
+                vertex,
+            } => write!(f, "vertex {} is not finite", vertex),
+            MeshError::Parse {
+                line,
+                message,
+            } => write!(f, "line {}: {}", line, message),
+            MeshError::Empty => write!(f, "mesh has no triangles"),
+        }
+    }
+}
+
+impl Error for MeshError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MeshError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MeshError {
+    fn from(error: io::Error) -> Self {
+        MeshError::Io(error)
+    }
+}
+
+#[derive(Debug)]
+pub enum BvhError {
+    Io(io::Error),
+    // the file doesn't start with the BVH magic bytes
+    NotABvh,
+    UnsupportedVersion(u8),
+    // a node or triangle refers to something that doesn't exist
+    IndexOutOfBounds {
+        what: &'static str,
+        index: usize,
+        len: usize,
+    },
+    // children have to come after their parents, and the tree can't be too deep to traverse
+    InvalidTree,
+    InvalidSurface(String),
+    Empty,
+}
+
+impl fmt::Display for BvhError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BvhError::Io(error) => write!(f, "problem reading BVH: {}", error),
+            BvhError::NotABvh => write!(f, "not a BVH file"),
+            BvhError::UnsupportedVersion(version) => write!(f, "unsupported BVH version {}", version),
+            BvhError::IndexOutOfBounds {
+                what,
+                index,
+                len,
+            } => write!(f, "{} index {} is out of bounds, there are only {}", what, index, len),
+            BvhError::InvalidTree => write!(f, "the BVH's nodes don't form a valid tree"),
+            BvhError::InvalidSurface(message) => write!(f, "invalid surface: {}", message),
+            BvhError::Empty => write!(f, "BVH has no nodes"),
+        }
+    }
+}
+
+impl Error for BvhError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BvhError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BvhError {
+    fn from(error: io::Error) -> Self {
+        BvhError::Io(error)
+    }
+}
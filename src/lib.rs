@@ -1,111 +1,194 @@
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Cursor, ErrorKind};
+use error::MeshError;
+#[cfg(not(feature = "prebuilt-bvh"))]
+use std::io::Cursor;
+use std::sync::Arc;
 
+#[cfg(feature = "cdylib")]
+pub mod capi;
+pub mod error;
 pub mod linear_algebra;
+#[cfg(feature = "python")]
+pub mod python;
 pub mod simulation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+// the vector type in the public API, re-exported so that callers don't have to match the version
+pub use glam;
+
+use glam::Vec3;
 use simulation::ball::Ball;
-use simulation::field::{initialize_dropshot, initialize_hoops, initialize_soccar, initialize_throwback};
-use simulation::game::Game;
+use simulation::bvh::Bvh;
+use simulation::dropshot::{DropshotBall, DropshotTiles};
+use simulation::field::initialize_custom;
+#[cfg(not(feature = "prebuilt-bvh"))]
+use simulation::field::{initialize_dropshot, initialize_hoops, initialize_soccar, initialize_throwback, InitializeThrowbackParams};
+use simulation::game::{Arena, Game};
+use simulation::goal::Goal;
+use simulation::heatseeker::Heatseeker;
 use simulation::mesh::Mesh;
-use vvec3::Vec3;
-
-use crate::simulation::field::InitializeThrowbackParams;
+use simulation::puck::Puck;
 
+#[cfg(not(feature = "prebuilt-bvh"))]
 fn read_mesh(ids_dat: Vec<u8>, vertices_dat: Vec<u8>) -> Mesh {
-    let mut ids_dat = Cursor::new(ids_dat);
-    let mut vertices_dat = Cursor::new(vertices_dat);
-    let mut ids: Vec<i32> = Vec::new();
-    let mut vertices: Vec<f32> = Vec::new();
-
-    loop {
-        ids.push(match ids_dat.read_i32::<LittleEndian>() {
-            Ok(num) => num,
-            Err(error) => match error.kind() {
-                ErrorKind::UnexpectedEof => break,
-                other_error => {
-                    panic!("Problem parsing file: {:?}", other_error)
-                }
-            },
-        });
-    }
-
-    loop {
-        vertices.push(match vertices_dat.read_f32::<LittleEndian>() {
-            Ok(num) => num,
-            Err(error) => match error.kind() {
-                ErrorKind::UnexpectedEof => break,
-                other_error => {
-                    panic!("Problem parsing file: {:?}", other_error)
-                }
-            },
-        });
+    match Mesh::from_bin(Cursor::new(ids_dat), Cursor::new(vertices_dat)) {
+        Ok(mesh) => mesh,
+        Err(error) => panic!("Problem parsing file: {}", error),
     }
+}
 
-    Mesh {
-        ids,
-        vertices,
+// Tree built ahead of time by examples/prebuild_bvh.rs, with the SAH builder
+#[cfg(feature = "prebuilt-bvh")]
+fn read_bvh(bvh: &[u8]) -> Bvh {
+    match Bvh::from_bin(bvh) {
+        Ok(bvh) => bvh,
+        Err(error) => panic!("Problem parsing prebuilt BVH: {}", error),
     }
 }
 
-pub fn load_soccar() -> Game {
+#[cfg(not(feature = "prebuilt-bvh"))]
+fn build_soccar() -> Bvh {
     let soccar_corner: Mesh = read_mesh(include_bytes!("../assets/soccar/soccar_corner_ids.bin").to_vec(), include_bytes!("../assets/soccar/soccar_corner_vertices.bin").to_vec());
     let soccar_goal: Mesh = read_mesh(include_bytes!("../assets/soccar/soccar_goal_ids.bin").to_vec(), include_bytes!("../assets/soccar/soccar_goal_vertices.bin").to_vec());
     let soccar_ramps_0: Mesh = read_mesh(include_bytes!("../assets/soccar/soccar_ramps_0_ids.bin").to_vec(), include_bytes!("../assets/soccar/soccar_ramps_0_vertices.bin").to_vec());
     let soccar_ramps_1: Mesh = read_mesh(include_bytes!("../assets/soccar/soccar_ramps_1_ids.bin").to_vec(), include_bytes!("../assets/soccar/soccar_ramps_1_vertices.bin").to_vec());
 
-    let collision_mesh = initialize_soccar(&soccar_corner, &soccar_goal, &soccar_ramps_0, &soccar_ramps_1);
+    initialize_soccar(&soccar_corner, &soccar_goal, &soccar_ramps_0, &soccar_ramps_1)
+}
 
+pub fn load_soccar() -> Game {
+    #[cfg(feature = "prebuilt-bvh")]
+    let collision_mesh = read_bvh(include_bytes!("../assets/prebuilt/soccar.bvh"));
+    #[cfg(not(feature = "prebuilt-bvh"))]
+    let collision_mesh = build_soccar();
+
+    soccar_game(collision_mesh)
+}
+
+// The soccar arena around its collision mesh, which is either built from the meshes or read from a prebuilt tree
+fn soccar_game(collision_mesh: Bvh) -> Game {
     let ball = Ball::initialize_soccar();
 
     let gravity = Vec3::new(0., 0., -650.);
 
+    let goals = Goal::soccar();
+
     Game {
         gravity,
-        collision_mesh,
+        arena: Arc::new(Arena {
+            collision_mesh,
+            goals,
+        }),
         ball,
+        ..Default::default()
     }
 }
 
-pub fn load_hoops() -> Game {
+// Heatseeker is played in the standard soccar arena, the ball starts seeking a goal after its first touch
+pub fn load_heatseeker() -> Game {
+    let mut game = load_soccar();
+
+    make_heatseeker(&mut game);
+
+    game
+}
+
+// Also used by the wasm bindings, which read the soccar arena from a prebuilt tree
+fn make_heatseeker(game: &mut Game) {
+    game.ball.heatseeker = Some(Heatseeker::default());
+    // the soccar speed cap would stop the ball short of the speed that the heatseeker speeds it up to
+    game.ball_physics.v_max = game.ball_physics.v_max.max(Heatseeker::MAX_SPEED);
+}
+
+// Snow Day is predicted in the standard soccar arena, with a puck instead of a ball
+// There's no separate Snow Day mesh in the assets, so anything that differs in the game's Snow Day arena isn't simulated
+// Use `Puck::get_puck_prediction_struct` instead of `Ball::get_ball_prediction_struct`
+pub fn load_snowday() -> Game {
+    let mut game = load_soccar();
+
+    game.puck = Some(Puck::initialize_snowday());
+
+    game
+}
+
+#[cfg(not(feature = "prebuilt-bvh"))]
+fn build_hoops() -> Bvh {
     let hoops_corner: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_corner_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_corner_vertices.bin").to_vec());
     let hoops_net: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_net_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_net_vertices.bin").to_vec());
     let hoops_rim: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_rim_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_rim_vertices.bin").to_vec());
     let hoops_ramps_0: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_ramps_0_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_ramps_0_vertices.bin").to_vec());
     let hoops_ramps_1: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_ramps_1_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_ramps_1_vertices.bin").to_vec());
 
-    let collision_mesh = initialize_hoops(&hoops_corner, &hoops_net, &hoops_rim, &hoops_ramps_0, &hoops_ramps_1);
+    initialize_hoops(&hoops_corner, &hoops_net, &hoops_rim, &hoops_ramps_0, &hoops_ramps_1)
+}
 
+pub fn load_hoops() -> Game {
+    #[cfg(feature = "prebuilt-bvh")]
+    let collision_mesh = read_bvh(include_bytes!("../assets/prebuilt/hoops.bvh"));
+    #[cfg(not(feature = "prebuilt-bvh"))]
+    let collision_mesh = build_hoops();
+
+    hoops_game(collision_mesh)
+}
+
+// The hoops arena around its collision mesh
+fn hoops_game(collision_mesh: Bvh) -> Game {
     let ball = Ball::initialize_hoops();
 
     let gravity = Vec3::new(0., 0., -650.);
 
+    let goals = Goal::hoops();
+
     Game {
         gravity,
-        collision_mesh,
+        arena: Arc::new(Arena {
+            collision_mesh,
+            goals,
+        }),
         ball,
+        ..Default::default()
     }
 }
 
-pub fn load_dropshot() -> Game {
+#[cfg(not(feature = "prebuilt-bvh"))]
+fn build_dropshot() -> Bvh {
     let dropshot: Mesh = read_mesh(include_bytes!("../assets/dropshot/dropshot_ids.bin").to_vec(), include_bytes!("../assets/dropshot/dropshot_vertices.bin").to_vec());
 
-    let collision_mesh = initialize_dropshot(&dropshot);
+    initialize_dropshot(&dropshot)
+}
+
+pub fn load_dropshot() -> Game {
+    #[cfg(feature = "prebuilt-bvh")]
+    let collision_mesh = read_bvh(include_bytes!("../assets/prebuilt/dropshot.bvh"));
+    #[cfg(not(feature = "prebuilt-bvh"))]
+    let collision_mesh = build_dropshot();
+
+    dropshot_game(collision_mesh)
+}
 
-    let ball = Ball::initialize_dropshot();
+// The dropshot arena around its collision mesh
+fn dropshot_game(collision_mesh: Bvh) -> Game {
+    let mut ball = Ball::initialize_dropshot();
+    ball.dropshot = Some(DropshotBall::default());
 
     let gravity = Vec3::new(0., 0., -650.);
 
+    let goals = Goal::dropshot();
+
     Game {
         gravity,
-        collision_mesh,
+        arena: Arc::new(Arena {
+            collision_mesh,
+            goals,
+        }),
         ball,
+        dropshot_tiles: Some(DropshotTiles::default()),
+        ..Default::default()
     }
 }
 
-pub fn load_soccar_throwback() -> Game {
-    println!("WARNING: THIS MAP IS KNOWN TO CAUSE EXTREME LAG WHEN GENERATING THE BALL PREDICTION STRUCT.");
-
+#[cfg(not(feature = "prebuilt-bvh"))]
+fn build_throwback() -> Bvh {
     let back_ramps_lower: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_back_ramps_lower_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_back_ramps_lower_vertices.bin").to_vec());
     let back_ramps_upper: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_back_ramps_upper_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_back_ramps_upper_vertices.bin").to_vec());
     let corner_ramps_lower: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_corner_ramps_lower_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_corner_ramps_lower_vertices.bin").to_vec());
@@ -129,15 +212,50 @@ pub fn load_soccar_throwback() -> Game {
         side_ramps_lower: &side_ramps_lower,
         side_ramps_upper: &side_ramps_upper,
     };
-    let collision_mesh = initialize_throwback(params);
+    initialize_throwback(params)
+}
+
+// This map is known to cause extreme lag when generating the ball prediction struct
+pub fn load_soccar_throwback() -> Game {
+    #[cfg(feature = "prebuilt-bvh")]
+    let collision_mesh = read_bvh(include_bytes!("../assets/prebuilt/throwback.bvh"));
+    #[cfg(not(feature = "prebuilt-bvh"))]
+    let collision_mesh = build_throwback();
+
+    throwback_game(collision_mesh)
+}
 
+// The throwback stadium around its collision mesh
+fn throwback_game(collision_mesh: Bvh) -> Game {
     let ball = Ball::initialize_soccar();
 
     let gravity = Vec3::new(0., 0., -650.);
 
+    let goals = Goal::throwback();
+
     Game {
         gravity,
-        collision_mesh,
+        arena: Arc::new(Arena {
+            collision_mesh,
+            goals,
+        }),
         ball,
+        ..Default::default()
     }
 }
+
+// Builds a game from meshes that were loaded at runtime, like workshop maps or custom training arenas
+// The ball is placed at the center of the world, so it should usually be moved with `game.ball.update`
+pub fn load_custom(meshes: &[Mesh], ball: Ball, gravity: Vec3) -> Result<Game, MeshError> {
+    let collision_mesh = initialize_custom(meshes)?;
+
+    Ok(Game {
+        gravity,
+        arena: Arc::new(Arena {
+            collision_mesh,
+            goals: Vec::new(),
+        }),
+        ball,
+        ..Default::default()
+    })
+}
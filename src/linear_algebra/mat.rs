@@ -1,3 +1,5 @@
+use std::ops::{Add, Sub};
+
 #[derive(Clone, Copy, Debug)]
 pub struct Mat3 {
     pub m: [[f32; 3]; 3],
@@ -48,4 +50,60 @@ impl Mat3 {
             ],
         }
     }
+
+    pub fn transpose(self) -> Mat3 {
+        let mut c: Mat3 = Mat3::default();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                c.m[i][j] = self.m[j][i];
+            }
+        }
+
+        c
+    }
+
+    pub fn scale(self, s: f32) -> Mat3 {
+        let mut c: Mat3 = Mat3::default();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                c.m[i][j] = self.m[i][j] * s;
+            }
+        }
+
+        c
+    }
+}
+
+impl Add for Mat3 {
+    type Output = Mat3;
+
+    fn add(self, b: Mat3) -> Mat3 {
+        let mut c: Mat3 = Mat3::default();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                c.m[i][j] = self.m[i][j] + b.m[i][j];
+            }
+        }
+
+        c
+    }
+}
+
+impl Sub for Mat3 {
+    type Output = Mat3;
+
+    fn sub(self, b: Mat3) -> Mat3 {
+        let mut c: Mat3 = Mat3::default();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                c.m[i][j] = self.m[i][j] - b.m[i][j];
+            }
+        }
+
+        c
+    }
 }
@@ -1,8 +1,8 @@
 use super::mat::Mat3;
-use vvec3::Vec3;
+use glam::Vec3;
 
 pub fn axis_to_rotation(vec: Vec3) -> Mat3 {
-    let norm_omega = vec.magnitude();
+    let norm_omega = vec.length();
 
     if norm_omega.abs() < 0.000001 {
         return Mat3::eye();
@@ -20,3 +20,10 @@ pub fn axis_to_rotation(vec: Vec3) -> Mat3 {
 pub fn dot(a: Mat3, v: Vec3) -> Vec3 {
     Vec3::new(a.m[0][0] * v.x + a.m[0][1] * v.y + a.m[0][2] * v.z, a.m[1][0] * v.x + a.m[1][1] * v.y + a.m[1][2] * v.z, a.m[2][0] * v.x + a.m[2][1] * v.y + a.m[2][2] * v.z)
 }
+
+// the matrix form of the cross product, dot(antisym(a), b) == a.cross(b)
+pub fn antisym(w: Vec3) -> Mat3 {
+    Mat3 {
+        m: [[0., -w.z, w.y], [w.z, 0., -w.x], [-w.y, w.x, 0.]],
+    }
+}
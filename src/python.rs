@@ -0,0 +1,216 @@
+// Python bindings, enabled by the `python` feature and built with maturin, see the README
+// Predictions are returned as NumPy arrays, vectors are an extra axis of 3 at the end
+
+use crate::simulation::ball::Ball;
+use crate::simulation::game::Game;
+use crate::{load_dropshot, load_heatseeker, load_hoops, load_soccar, load_soccar_throwback};
+use glam::Vec3;
+use numpy::{PyArray1, PyArrayDyn, PyArrayMethods, PyReadonlyArray1, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+fn to_array(v: Vec3) -> [f32; 3] {
+    [v.x, v.y, v.z]
+}
+
+fn from_array(v: [f32; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+#[pyclass(name = "Ball", frozen)]
+#[derive(Clone, Copy)]
+pub struct PyBall(Ball);
+
+#[pymethods]
+impl PyBall {
+    #[getter]
+    fn time(&self) -> f32 {
+        self.0.time
+    }
+
+    #[getter]
+    fn location(&self) -> [f32; 3] {
+        to_array(self.0.location)
+    }
+
+    #[getter]
+    fn velocity(&self) -> [f32; 3] {
+        to_array(self.0.velocity)
+    }
+
+    #[getter]
+    fn angular_velocity(&self) -> [f32; 3] {
+        to_array(self.0.angular_velocity)
+    }
+
+    #[getter]
+    fn radius(&self) -> f32 {
+        self.0.radius
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Ball(time={}, location={:?}, velocity={:?}, angular_velocity={:?})", self.0.time, self.location(), self.velocity(), self.angular_velocity())
+    }
+}
+
+// The slices of one prediction, or of a batch of predictions that all have the same number of slices
+#[pyclass(name = "BallPrediction", frozen)]
+pub struct PyBallPrediction {
+    // [slices] for one prediction, [starts, slices] for a batch
+    shape: Vec<usize>,
+    slices: Vec<Ball>,
+}
+
+impl PyBallPrediction {
+    fn scalars<'py>(&self, py: Python<'py>, f: impl Fn(&Ball) -> f32) -> PyResult<Bound<'py, PyArrayDyn<f32>>> {
+        PyArray1::from_vec(py, self.slices.iter().map(f).collect()).reshape(self.shape.clone())
+    }
+
+    fn vectors<'py>(&self, py: Python<'py>, f: impl Fn(&Ball) -> Vec3) -> PyResult<Bound<'py, PyArrayDyn<f32>>> {
+        let mut shape = self.shape.clone();
+        shape.push(3);
+
+        PyArray1::from_vec(py, self.slices.iter().flat_map(|ball| to_array(f(ball))).collect()).reshape(shape)
+    }
+}
+
+#[pymethods]
+impl PyBallPrediction {
+    #[getter]
+    fn num_slices(&self) -> usize {
+        self.shape[self.shape.len() - 1]
+    }
+
+    #[getter]
+    fn time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArrayDyn<f32>>> {
+        self.scalars(py, |ball| ball.time)
+    }
+
+    #[getter]
+    fn location<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArrayDyn<f32>>> {
+        self.vectors(py, |ball| ball.location)
+    }
+
+    #[getter]
+    fn velocity<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArrayDyn<f32>>> {
+        self.vectors(py, |ball| ball.velocity)
+    }
+
+    #[getter]
+    fn angular_velocity<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArrayDyn<f32>>> {
+        self.vectors(py, |ball| ball.angular_velocity)
+    }
+
+    fn __len__(&self) -> usize {
+        self.shape[0]
+    }
+}
+
+#[pyclass(name = "Game")]
+pub struct PyGame(Game);
+
+#[pymethods]
+impl PyGame {
+    #[getter]
+    fn ball(&self) -> PyBall {
+        PyBall(self.0.ball)
+    }
+
+    #[pyo3(signature = (time, location, velocity, angular_velocity = [0.; 3]))]
+    fn set_ball(&mut self, time: f32, location: [f32; 3], velocity: [f32; 3], angular_velocity: [f32; 3]) {
+        self.0.ball.update(time, from_array(location), from_array(velocity), from_array(angular_velocity));
+    }
+
+    // Predicts `seconds` of the ball's path from its current state, 120 slices a second
+    // The ball's own state is left as it was
+    #[pyo3(signature = (seconds = 6.))]
+    fn predict(&mut self, py: Python<'_>, seconds: f32) -> PyBallPrediction {
+        let game = &mut self.0;
+        let start = game.ball;
+
+        let slices = py.detach(|| Ball::get_ball_prediction_struct_for_time(game, &seconds).slices);
+        game.ball = start;
+
+        PyBallPrediction {
+            shape: vec![slices.len()],
+            slices,
+        }
+    }
+
+    // Predicts `seconds` of the ball's path from each of the start states, which are rows of the arrays
+    // The ball's own state is left as it was
+    #[pyo3(signature = (time, location, velocity, angular_velocity, seconds = 6.))]
+    fn predict_batch(&mut self, py: Python<'_>, time: PyReadonlyArray1<f32>, location: PyReadonlyArray2<f32>, velocity: PyReadonlyArray2<f32>, angular_velocity: PyReadonlyArray2<f32>, seconds: f32) -> PyResult<PyBallPrediction> {
+        let (time, location, velocity, angular_velocity) = (time.as_array(), location.as_array(), velocity.as_array(), angular_velocity.as_array());
+        let starts = time.len();
+
+        for (name, array) in [("location", &location), ("velocity", &velocity), ("angular_velocity", &angular_velocity)] {
+            if array.shape() != [starts, 3] {
+                return Err(PyValueError::new_err(format!("{name} has the shape {:?}, it should be ({starts}, 3)", array.shape())));
+            }
+        }
+
+        let game = &mut self.0;
+        let start = game.ball;
+        let num_slices = (seconds / Ball::SIMULATION_DT).round() as usize;
+
+        let slices = py.detach(|| {
+            let mut slices = Vec::with_capacity(starts * num_slices);
+            let row = |array: &numpy::ndarray::ArrayView2<f32>, i: usize| Vec3::new(array[[i, 0]], array[[i, 1]], array[[i, 2]]);
+
+            for i in 0..starts {
+                // every row starts from the same ball, so that state which changes during a prediction, like the heatseeker's, isn't carried over
+                game.ball = start;
+                game.ball.update(time[i], row(&location, i), row(&velocity, i), row(&angular_velocity, i));
+                slices.extend(Ball::get_ball_prediction_struct_for_slices(game, num_slices).slices);
+            }
+
+            game.ball = start;
+            slices
+        });
+
+        Ok(PyBallPrediction {
+            shape: vec![starts, num_slices],
+            slices,
+        })
+    }
+}
+
+#[pyfunction(name = "load_soccar")]
+fn py_load_soccar() -> PyGame {
+    PyGame(load_soccar())
+}
+
+#[pyfunction(name = "load_heatseeker")]
+fn py_load_heatseeker() -> PyGame {
+    PyGame(load_heatseeker())
+}
+
+#[pyfunction(name = "load_hoops")]
+fn py_load_hoops() -> PyGame {
+    PyGame(load_hoops())
+}
+
+#[pyfunction(name = "load_dropshot")]
+fn py_load_dropshot() -> PyGame {
+    PyGame(load_dropshot())
+}
+
+#[pyfunction(name = "load_soccar_throwback")]
+fn py_load_soccar_throwback() -> PyGame {
+    PyGame(load_soccar_throwback())
+}
+
+#[pymodule]
+fn rl_ball_sym(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGame>()?;
+    m.add_class::<PyBall>()?;
+    m.add_class::<PyBallPrediction>()?;
+    m.add_function(wrap_pyfunction!(py_load_soccar, m)?)?;
+    m.add_function(wrap_pyfunction!(py_load_heatseeker, m)?)?;
+    m.add_function(wrap_pyfunction!(py_load_hoops, m)?)?;
+    m.add_function(wrap_pyfunction!(py_load_dropshot, m)?)?;
+    m.add_function(wrap_pyfunction!(py_load_soccar_throwback, m)?)?;
+
+    Ok(())
+}
@@ -1,7 +1,15 @@
 pub mod ball;
+pub mod bullet;
 pub mod bvh;
+pub mod car;
+pub mod dropshot;
 pub mod field;
 pub mod game;
 pub mod geometry;
+pub mod goal;
+pub mod heatseeker;
 pub mod mesh;
 pub mod morton;
+pub mod mutators;
+pub mod prediction;
+pub mod puck;
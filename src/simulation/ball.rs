@@ -1,6 +1,14 @@
+use crate::linear_algebra::mat::Mat3;
+use crate::linear_algebra::math::{antisym, dot};
+use crate::simulation::bullet;
+use crate::simulation::bvh::Bvh;
+use crate::simulation::car::Car;
+use crate::simulation::dropshot::{DropshotBall, TileDamage, TileState};
 use crate::simulation::game::Game;
-use crate::simulation::geometry::Sphere;
-use vvec3::Vec3;
+use crate::simulation::geometry::{Contact, ContactPoint, Sphere, Surface, SurfaceKind, SweepHit, Tri};
+use crate::simulation::goal::{Goal, GoalEvent};
+use crate::simulation::heatseeker::Heatseeker;
+use glam::Vec3;
 
 #[derive(Clone, Copy, Debug)]
 pub struct Ball {
@@ -11,6 +19,10 @@ pub struct Ball {
     pub radius: f32,
     pub collision_radius: f32,
     pub moi: f32,
+    // only set in heatseeker games
+    pub heatseeker: Option<Heatseeker>,
+    // only set in dropshot games
+    pub dropshot: Option<DropshotBall>,
 }
 
 impl Default for Ball {
@@ -23,14 +35,75 @@ impl Default for Ball {
             radius: 0.,
             collision_radius: 0.,
             moi: 0.,
+            heatseeker: None,
+            dropshot: None,
         }
     }
 }
 
+// The physics constants of the ball, these can be changed by mutators
+#[derive(Clone, Copy, Debug)]
+pub struct BallPhysicsParams {
+    pub restitution: f32,
+    pub drag: f32,
+    pub mu: f32,
+    pub v_max: f32,
+    pub w_max: f32,
+    pub m: f32,
+}
+
+impl Default for BallPhysicsParams {
+    fn default() -> Self {
+        Self {
+            restitution: Ball::RESTITUTION,
+            drag: Ball::DRAG,
+            mu: Ball::MU,
+            v_max: Ball::V_MAX,
+            w_max: Ball::W_MAX,
+            m: Ball::M,
+        }
+    }
+}
+
+// How Ball::step handles the ball touching more than one surface at once
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BallSolver {
+    // every touched triangle is averaged into one contact, cheap but the normal is wrong in corners and at seams, this is the default
+    SingleContact,
+    // every touched triangle is its own contact, see Bvh::collide_manifold
+    MultiContact,
+    // an approximation of the step of Bullet, the physics engine that Rocket League uses, see bullet::step_ball
+    // it hasn't been checked against recordings of the game
+    // continuous collision isn't used, contacts are found slightly before the ball touches them instead
+    Bullet,
+}
+
+impl Default for BallSolver {
+    fn default() -> Self {
+        BallSolver::SingleContact
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BallContact {
+    pub time: f32,
+    pub location: Vec3,
+    pub normal: Vec3,
+    pub velocity_before: Vec3,
+    pub velocity_after: Vec3,
+    pub surface: Surface,
+}
+
 #[derive(Clone)]
 pub struct BallPrediction {
     pub num_slices: usize,
     pub slices: Vec<Ball>,
+    pub goals: Vec<GoalEvent>,
+    // only recorded if game.record_contacts is true
+    // a rolling ball is in contact with the ground every slice
+    pub contacts: Option<Vec<BallContact>>,
+    // the tiles that each floor hit would damage, only in dropshot games
+    pub tile_damage: Vec<TileDamage>,
 }
 
 impl Default for BallPrediction {
@@ -38,6 +111,9 @@ impl Default for BallPrediction {
         Self {
             num_slices: 0,
             slices: Vec::new(),
+            goals: Vec::new(),
+            contacts: None,
+            tile_damage: Vec::new(),
         }
     }
 }
@@ -59,10 +135,7 @@ impl Ball {
     const HOOPS_COLLISION_RADIUS: f32 = 93.15;
     const DROPSHOT_COLLISION_RADIUS: f32 = 103.6;
 
-    const INV_M: f32 = 1. / 30.;
-    const RESTITUTION_M: f32 = -(1. + Ball::RESTITUTION) * Ball::M;
-
-    const SIMULATION_DT: f32 = 1. / 120.;
+    pub const SIMULATION_DT: f32 = 1. / 120.;
     const STANDARD_NUM_SLICES: usize = 720;
 
     pub fn initialize_soccar() -> Self {
@@ -101,13 +174,29 @@ impl Ball {
         ball
     }
 
+    pub fn initialize_custom(radius: f32, collision_radius: f32) -> Self {
+        let mut ball = Ball {
+            radius,
+            collision_radius,
+            ..Default::default()
+        };
+
+        ball.calculate_moi();
+
+        ball
+    }
+
     pub fn initialize(&mut self) {
         self.location.z = 1.1 * self.collision_radius;
         self.calculate_moi();
     }
 
     pub fn calculate_moi(&mut self) {
-        self.moi = 0.4 * Ball::M * self.radius * self.radius;
+        self.calculate_moi_with_mass(Ball::M);
+    }
+
+    pub fn calculate_moi_with_mass(&mut self, m: f32) {
+        self.moi = 0.4 * m * self.radius * self.radius;
     }
 
     pub fn update(&mut self, time: f32, location: Vec3, velocity: Vec3, angular_velocity: Vec3) {
@@ -117,51 +206,239 @@ impl Ball {
         self.angular_velocity = angular_velocity;
     }
 
-    fn hitbox(&self) -> Sphere {
+    pub fn hitbox(&self) -> Sphere {
         Sphere {
             center: self.location,
             radius: self.collision_radius,
         }
     }
 
-    pub fn step(game: &mut Game, dt: f32) {
-        match game.collision_mesh.collide(&game.ball.hitbox()) {
-            Some(contact) => {
-                let p = contact.start;
-                let n = contact.direction;
+    // Psyonix's extra impulse, scaled down as the relative speed of the car and ball goes up
+    fn psyonix_scale(dv: f32) -> f32 {
+        if dv <= 500. {
+            0.65
+        } else if dv <= 2300. {
+            0.65 - 0.1 * (dv - 500.) / 1800.
+        } else {
+            0.55 - 0.25 * (dv - 2300.) / 2300.
+        }
+    }
 
-                let loc = p - game.ball.location;
+    // Applies the impulse from a car hitting the ball
+    // Returns false if the car's hitbox isn't touching the ball
+    pub fn collide_car(&mut self, car: &Car, params: &BallPhysicsParams) -> bool {
+        let hitbox = car.hitbox();
+        let p = hitbox.closest_point(self.location);
 
-                let m_reduced = 1. / (Ball::INV_M + loc.dot(&loc) / game.ball.moi);
+        let n1 = p - self.location;
+        if n1.length() >= self.collision_radius {
+            return false;
+        }
 
-                let v_perp = n * game.ball.velocity.dot(&n).min(0.);
-                let v_para = game.ball.velocity - v_perp - loc.cross(&game.ball.angular_velocity);
+        // a ball center that's inside of the hitbox is its own closest point, so point at the hitbox's center instead
+        let n1 = n1.normalize_or((hitbox.center - self.location).normalize_or(car.up() * -1.));
 
-                let ratio = v_perp.magnitude() / v_para.magnitude().max(0.0001);
+        let l_b = p - self.location;
+        let l_c = p - car.location;
 
-                let j_perp = v_perp * Ball::RESTITUTION_M;
-                let j_para = -(Ball::MU * ratio).min(1.) * m_reduced * v_para;
+        let inv_i_c = car.inv_inertia();
 
-                let j = j_perp + j_para;
+        let k_b = antisym(l_b).dot(antisym(l_b)).scale(1. / self.moi);
+        let k_c = antisym(l_c).dot(inv_i_c).dot(antisym(l_c));
+        let m = (Mat3::eye().scale(1. / params.m + 1. / Car::M) - k_b - k_c).inv();
 
-                game.ball.angular_velocity += loc.cross(&j) / game.ball.moi;
-                game.ball.velocity += (j / Ball::M) + game.ball.velocity * (Ball::DRAG * dt);
-                game.ball.location += game.ball.velocity * dt;
+        let delta_v = (car.velocity - l_c.cross(car.angular_velocity)) - (self.velocity - l_b.cross(self.angular_velocity));
+
+        // the impulse that is consistent with an inelastic collision
+        let j1 = dot(m, delta_v);
+        let j1_perp = n1 * j1.dot(n1).min(-1.);
+        let j1_para = j1 - j1_perp;
+
+        // scale the parallel component of j1 so that the Coulomb friction model is satisfied
+        let ratio = j1_perp.length() / j1_para.length().max(0.001);
+        let j1 = j1_perp + j1_para * (params.mu * ratio).min(1.);
+
+        let f = car.forward();
+        let mut n2 = self.location - hitbox.center;
+        n2.z *= 0.35;
+        let n2 = (n2 - f * (0.35 * n2.dot(f))).normalize_or(car.up());
+
+        let dv = (car.velocity - self.velocity).length().min(4600.);
+        let j2 = n2 * (params.m * dv * Ball::psyonix_scale(dv));
+
+        self.angular_velocity += l_b.cross(j1) / self.moi;
+        self.velocity += (j1 + j2) / params.m;
+
+        if let Some(heatseeker) = &mut self.heatseeker {
+            heatseeker.on_touch(car.team);
+        }
+
+        if let Some(dropshot) = &mut self.dropshot {
+            dropshot.on_touch(car.team, dv);
+        }
+
+        true
+    }
+
+    // The impulse that the arena applies to the ball at the point `p` on a surface with the normal `n`
+    fn contact_impulse(&self, params: &BallPhysicsParams, p: Vec3, n: Vec3) -> Vec3 {
+        let loc = p - self.location;
+
+        let m_reduced = 1. / (1. / params.m + loc.dot(loc) / self.moi);
+
+        let v_perp = n * self.velocity.dot(n).min(0.);
+        let v_para = self.velocity - v_perp - loc.cross(self.angular_velocity);
+
+        let ratio = v_perp.length() / v_para.length().max(0.0001);
+
+        let j_perp = v_perp * (-(1. + params.restitution) * params.m);
+        let j_para = -(params.mu * ratio).min(1.) * m_reduced * v_para;
+
+        j_perp + j_para
+    }
+
+    fn resolve_contact(&mut self, params: &BallPhysicsParams, p: Vec3, n: Vec3, dt: f32) {
+        let loc = p - self.location;
+        let j = self.contact_impulse(params, p, n);
+
+        self.angular_velocity += loc.cross(j) / self.moi;
+        self.velocity += (j / params.m) + self.velocity * (params.drag * dt);
+        self.location += self.velocity * dt;
+
+        let penetration = self.collision_radius - (self.location - p).dot(n);
+        if penetration > 0. {
+            self.location += n * (1.001 * penetration);
+        }
+    }
+
+    // Applies the impulse of every contact in turn, deepest first, so each contact sees the velocity that the ones before it left
+    // A contact that the ball is already moving away from only applies friction
+    fn resolve_manifold(&mut self, params: &BallPhysicsParams, manifold: &[ContactPoint], dt: f32) {
+        let velocity = self.velocity;
+
+        for contact in manifold {
+            let loc = contact.point - self.location;
+            let j = self.contact_impulse(params, contact.point, contact.normal);
+
+            self.angular_velocity += loc.cross(j) / self.moi;
+            self.velocity += j / params.m;
+        }
+
+        self.velocity += velocity * (params.drag * dt);
+        self.location += self.velocity * dt;
+
+        for contact in manifold {
+            let penetration = self.collision_radius - (self.location - contact.point).dot(contact.normal);
+            if penetration > 0. {
+                self.location += contact.normal * (1.001 * penetration);
+            }
+        }
+    }
+
+    // Moves the ball up to the first thing that's in its way this step, if there is anything
+    fn sweep(&mut self, collision_mesh: &Bvh, gravity: Vec3, params: &BallPhysicsParams, dt: f32, keep: impl Fn(&Tri, Vec3) -> bool) -> Option<SweepHit> {
+        let d = (self.velocity + (self.velocity * params.drag + gravity) * dt) * dt;
+        let hit = collision_mesh.sweep_sphere_with(&self.hitbox(), d, keep)?;
+
+        self.location += d * hit.toi;
+
+        Some(hit)
+    }
+
+    pub fn step(game: &mut Game, dt: f32) -> Option<BallContact> {
+        let params = game.ball_physics;
+
+        if let Some(heatseeker) = &mut game.ball.heatseeker {
+            game.ball.velocity = heatseeker.seek(game.ball.location, game.ball.velocity, dt);
+        }
+
+        let velocity_before = game.ball.velocity;
+        let tiles = game.dropshot_tiles.as_ref();
+        // open tiles are holes in the floor
+        let keep = |tri: &Tri, p: Vec3| tri.surface.kind != SurfaceKind::Floor || tiles.is_none_or(|tiles| !tiles.is_open_at(p));
+
+        let contact = match game.ball_solver {
+            BallSolver::SingleContact => {
+                let mut contact = game.arena.collision_mesh.collide_with(&game.ball.hitbox(), keep);
+
+                // move the ball up to the first thing that's in its way and bounce off of it there
+                if contact.is_none() && game.continuous_collision {
+                    contact = game.ball.sweep(&game.arena.collision_mesh, game.gravity, &params, dt, keep).map(|hit| Contact {
+                        start: hit.point,
+                        direction: hit.normal,
+                        surface: hit.surface,
+                    });
+                }
+
+                if let Some(contact) = &contact {
+                    game.ball.resolve_contact(&params, contact.start, contact.direction, dt);
+                }
 
-                let penetration = game.ball.collision_radius - (game.ball.location - p).dot(&n);
-                if penetration > 0. {
-                    game.ball.location += n * (1.001 * penetration);
+                contact
+            }
+            BallSolver::MultiContact => {
+                let mut manifold = game.arena.collision_mesh.collide_manifold_with(&game.ball.hitbox(), keep);
+
+                if manifold.is_empty() && game.continuous_collision {
+                    manifold.extend(game.ball.sweep(&game.arena.collision_mesh, game.gravity, &params, dt, keep).map(|hit| ContactPoint {
+                        point: hit.point,
+                        normal: hit.normal,
+                        depth: 0.,
+                        surface: hit.surface,
+                    }));
+                }
+
+                if !manifold.is_empty() {
+                    game.ball.resolve_manifold(&params, &manifold, dt);
                 }
+
+                // the deepest contact is the one that's reported
+                manifold.first().map(|contact| Contact {
+                    start: contact.point,
+                    direction: contact.normal,
+                    surface: contact.surface.clone(),
+                })
             }
+            BallSolver::Bullet => {
+                let mut hitbox = game.ball.hitbox();
+                hitbox.radius += bullet::CONTACT_BREAKING_THRESHOLD;
+
+                let manifold = game.arena.collision_mesh.collide_manifold_with(&hitbox, keep);
+
+                bullet::step_ball(&mut game.ball, &params, game.gravity, &manifold, dt).map(|i| Contact {
+                    start: manifold[i].point,
+                    direction: manifold[i].normal,
+                    surface: manifold[i].surface.clone(),
+                })
+            }
+        };
+
+        match &contact {
+            Some(contact) => {
+                if let Some(heatseeker) = &mut game.ball.heatseeker {
+                    heatseeker.on_world_contact(game.ball.location, contact.direction);
+                }
+            }
+            // Bullet's step always moves the ball
+            None if game.ball_solver == BallSolver::Bullet => {}
             None => {
-                game.ball.velocity += (game.ball.velocity * Ball::DRAG + game.gravity) * dt;
+                game.ball.velocity += (game.ball.velocity * params.drag + game.gravity) * dt;
                 game.ball.location += game.ball.velocity * dt;
             }
         }
 
-        game.ball.angular_velocity *= (Ball::W_MAX / game.ball.angular_velocity.magnitude()).min(1.);
-        game.ball.velocity *= (Ball::V_MAX / game.ball.velocity.magnitude()).min(1.);
+        game.ball.angular_velocity *= (params.w_max / game.ball.angular_velocity.length()).min(1.);
+        game.ball.velocity *= (params.v_max / game.ball.velocity.length()).min(1.);
         game.ball.time += dt;
+
+        contact.map(|contact| BallContact {
+            time: game.ball.time,
+            location: contact.start,
+            normal: contact.direction,
+            velocity_before,
+            velocity_after: game.ball.velocity,
+            surface: contact.surface,
+        })
     }
 
     pub fn get_ball_prediction_struct_for_time(game: &mut Game, time: &f32) -> BallPrediction {
@@ -173,23 +450,106 @@ impl Ball {
     }
 
     pub fn get_ball_prediction_struct_for_slices(game: &mut Game, num_slices: usize) -> BallPrediction {
+        let start = game.ball;
+        let start_tiles = game.dropshot_tiles.clone();
         let mut slices = Vec::with_capacity(num_slices);
+        let mut contacts = if game.record_contacts {
+            Some(Vec::new())
+        } else {
+            None
+        };
+        let mut tile_damage = Vec::new();
 
-        for _ in 0..num_slices {
-            Ball::step(game, Ball::SIMULATION_DT);
+        for slice in 0..num_slices {
+            Ball::step_slice(game, slice, &mut contacts, &mut tile_damage);
             slices.push(game.ball);
         }
 
+        game.dropshot_tiles = start_tiles;
+
+        let goals = Goal::find_events(&game.arena.goals, &start, &slices, 0);
+
         BallPrediction {
             num_slices: slices.len(),
             slices,
+            goals,
+            contacts,
+            tile_damage,
+        }
+    }
+
+    // Steps the ball forward by one slice, recording the contact and the tiles that the ball damages
+    // The tiles in `game` are damaged too, so that the ball falls through the ones that it opens
+    pub fn step_slice(game: &mut Game, slice: usize, contacts: &mut Option<Vec<BallContact>>, tile_damage: &mut Vec<TileDamage>) {
+        let Some(contact) = Ball::step(game, Ball::SIMULATION_DT) else {
+            return;
+        };
+
+        if let (Some(dropshot), Some(tiles)) = (&mut game.ball.dropshot, &mut game.dropshot_tiles) {
+            if contact.surface.kind == SurfaceKind::Floor {
+                tile_damage.extend(dropshot.on_floor_contact(tiles, contact.location, contact.time, slice));
+            }
+        }
+
+        if let Some(contacts) = contacts {
+            contacts.push(contact);
         }
     }
+
+    // Applies a hypothetical car touch to the given slice of the prediction,
+    // then re-simulates every slice after it
+    // Returns false (and leaves the prediction untouched) if the car doesn't touch the ball
+    pub fn apply_touch(game: &mut Game, prediction: &mut BallPrediction, slice: usize, car: &Car) -> bool {
+        let Some(mut ball) = prediction.slices.get(slice).copied() else {
+            return false;
+        };
+
+        if !ball.collide_car(car, &game.ball_physics) {
+            return false;
+        }
+
+        game.ball = ball;
+        prediction.slices[slice] = ball;
+
+        if let Some(contacts) = &mut prediction.contacts {
+            contacts.retain(|contact| contact.time <= ball.time);
+        }
+
+        // put the tiles back in the state that they were in at the touched slice
+        let start_tiles = game.dropshot_tiles.clone();
+        prediction.tile_damage.retain(|damage| damage.slice <= slice);
+
+        if let Some(tiles) = &mut game.dropshot_tiles {
+            for damage in &prediction.tile_damage {
+                for &i in &damage.damaged {
+                    tiles.tiles[i].state = TileState::Damaged;
+                }
+
+                for &i in &damage.broken {
+                    tiles.tiles[i].state = TileState::Open;
+                }
+            }
+        }
+
+        for next_slice in slice + 1..prediction.slices.len() {
+            Ball::step_slice(game, next_slice, &mut prediction.contacts, &mut prediction.tile_damage);
+            prediction.slices[next_slice] = game.ball;
+        }
+
+        game.dropshot_tiles = start_tiles;
+
+        // the touch only changes the velocity, so the goals up to and including the touched slice still stand
+        prediction.goals.retain(|goal| goal.slice <= slice);
+        prediction.goals.extend(Goal::find_events(&game.arena.goals, &ball, &prediction.slices[slice + 1..], slice + 1));
+
+        true
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::load_soccar;
+    use crate::simulation::car::CarHitbox;
 
     use super::*;
 
@@ -228,4 +588,59 @@ mod test {
         assert_eq!(prediction.num_slices, predicted_slices);
         assert_eq!(prediction.slices.len(), predicted_slices);
     }
+
+    #[test]
+    fn check_car_touch() {
+        const TOUCH_SLICE: usize = 60;
+
+        let mut game = load_soccar();
+
+        let mut prediction = Ball::get_ball_prediction_struct(&mut game);
+        let ball = prediction.slices[TOUCH_SLICE];
+
+        // an octane driving into the ball along the y axis
+        let mut car = Car {
+            velocity: Vec3::new(0., 1400., 0.),
+            hitbox: CarHitbox::octane(),
+            ..Default::default()
+        };
+        car.orientation = Mat3 {
+            m: [[0., -1., 0.], [1., 0., 0.], [0., 0., 1.]],
+        };
+        car.location = ball.location - car.forward() * (car.hitbox.offset.x + car.hitbox.length / 2. + ball.collision_radius - 20.);
+        car.location.z = 17.;
+
+        assert!(Ball::apply_touch(&mut game, &mut prediction, TOUCH_SLICE, &car));
+
+        assert_eq!(prediction.num_slices, Ball::STANDARD_NUM_SLICES);
+        assert_eq!(prediction.slices.len(), Ball::STANDARD_NUM_SLICES);
+
+        let touched = prediction.slices[TOUCH_SLICE];
+        assert!(touched.velocity.y > car.velocity.y);
+        assert!(prediction.slices[TOUCH_SLICE + 1].location.y > touched.location.y);
+
+        // a car on the other side of the field doesn't touch the ball
+        car.location.y = 4000.;
+        assert!(!Ball::apply_touch(&mut game, &mut prediction, TOUCH_SLICE, &car));
+
+        // neither does a slice that isn't in the prediction
+        assert!(!Ball::apply_touch(&mut game, &mut prediction, Ball::STANDARD_NUM_SLICES, &car));
+    }
+
+    #[test]
+    fn check_car_touch_inside_hitbox() {
+        let mut ball = Ball::initialize_soccar();
+        ball.update(0., Vec3::new(0., 0., 100.), Vec3::default(), Vec3::default());
+
+        // the ball's center is the hitbox's center
+        let mut car = Car {
+            hitbox: CarHitbox::octane(),
+            ..Default::default()
+        };
+        car.location = ball.location - car.hitbox.offset;
+
+        assert!(ball.collide_car(&car, &BallPhysicsParams::default()));
+        assert!(ball.velocity.length().is_finite());
+        assert!(ball.angular_velocity.length().is_finite());
+    }
 }
@@ -0,0 +1,191 @@
+use crate::simulation::ball::{Ball, BallPhysicsParams};
+use crate::simulation::geometry::ContactPoint;
+use glam::Vec3;
+
+// An approximation of how Bullet, the physics engine that Rocket League uses, steps the ball, written from Bullet's source
+// None of it has been compared against recordings of the game, so it isn't known to be any closer to the game than the other solvers
+
+// Bullet's default solver settings, scaled to unreal units, the settings that the game uses haven't been checked
+// Rocket League's physics run in Bullet units that are 50 uu long, and Bullet's defaults are in those units
+const BULLET_SCALE: f32 = 50.;
+
+// contacts are generated for surfaces that are this close to the ball, even if the ball isn't touching them yet
+pub const CONTACT_BREAKING_THRESHOLD: f32 = 0.02 * BULLET_SCALE;
+// bounces slower than this don't use the restitution
+const RESTITUTION_VELOCITY_THRESHOLD: f32 = 0.2 * BULLET_SCALE;
+// penetration deeper than this is fixed with split impulses, which move the ball without changing its velocity
+const SPLIT_IMPULSE_PENETRATION_THRESHOLD: f32 = -0.04 * BULLET_SCALE;
+
+const NUM_ITERATIONS: usize = 10;
+const ERP: f32 = 0.2;
+const ERP2: f32 = 0.8;
+
+// Bullet multiplies the friction and the restitution of the two bodies in a contact, the ball's restitution is BallPhysicsParams::restitution
+// These are RocketSim's values, they haven't been checked against recordings of the game
+pub const BALL_FRICTION: f32 = 0.35;
+pub const ARENA_FRICTION: f32 = 0.6;
+pub const ARENA_RESTITUTION: f32 = 0.3;
+
+// A row of Bullet's sequential impulse solver, the impulse is applied along `direction` at `rel_pos` from the ball's center
+#[derive(Clone, Copy, Debug)]
+struct SolverRow {
+    direction: Vec3,
+    rel_pos_cross_direction: Vec3,
+    jac_diag_inv: f32,
+    rhs: f32,
+    rhs_penetration: f32,
+    applied_impulse: f32,
+    applied_push_impulse: f32,
+}
+
+impl SolverRow {
+    fn new(ball: &Ball, params: &BallPhysicsParams, direction: Vec3, rel_pos: Vec3) -> Self {
+        let rel_pos_cross_direction = rel_pos.cross(direction);
+
+        Self {
+            direction,
+            rel_pos_cross_direction,
+            jac_diag_inv: 1. / (1. / params.m + rel_pos_cross_direction.dot(rel_pos_cross_direction) / ball.moi),
+            rhs: 0.,
+            rhs_penetration: 0.,
+            applied_impulse: 0.,
+            applied_push_impulse: 0.,
+        }
+    }
+
+    // How fast the ball's contact point is moving along the row's direction
+    fn velocity(&self, linear: Vec3, angular: Vec3) -> f32 {
+        self.direction.dot(linear) + self.rel_pos_cross_direction.dot(angular)
+    }
+
+    // Bullet's resolveSingleConstraintRowGeneric, the impulse is clamped to [lower, upper] and applied to the velocity deltas
+    fn solve(&mut self, ball: &Ball, params: &BallPhysicsParams, lower: f32, upper: f32, delta_linear: &mut Vec3, delta_angular: &mut Vec3) {
+        let delta_impulse = self.rhs - self.velocity(*delta_linear, *delta_angular) * self.jac_diag_inv;
+        let applied_impulse = (self.applied_impulse + delta_impulse).clamp(lower, upper);
+        let delta_impulse = applied_impulse - self.applied_impulse;
+        self.applied_impulse = applied_impulse;
+
+        *delta_linear += self.direction * (delta_impulse / params.m);
+        *delta_angular += self.rel_pos_cross_direction * (delta_impulse / ball.moi);
+    }
+
+    // Bullet's resolveSplitPenetrationImpulse, like `solve` but for the push velocities
+    fn solve_penetration(&mut self, ball: &Ball, params: &BallPhysicsParams, push_linear: &mut Vec3, push_angular: &mut Vec3) {
+        if self.rhs_penetration == 0. {
+            return;
+        }
+
+        let delta_impulse = self.rhs_penetration - self.velocity(*push_linear, *push_angular) * self.jac_diag_inv;
+        let applied_push_impulse = (self.applied_push_impulse + delta_impulse).max(0.);
+        let delta_impulse = applied_push_impulse - self.applied_push_impulse;
+        self.applied_push_impulse = applied_push_impulse;
+
+        *push_linear += self.direction * (delta_impulse / params.m);
+        *push_angular += self.rel_pos_cross_direction * (delta_impulse / ball.moi);
+    }
+}
+
+// One step of the ball, following the order of Bullet's btDiscreteDynamicsWorld::stepSimulation:
+// damping, gravity, a sequential impulse solve of the contacts found at the start of the step, and then the position is integrated
+// The contacts' depths are measured from a sphere that is CONTACT_BREAKING_THRESHOLD bigger than the ball
+// Contacts aren't kept between steps, so there's no warm starting
+// Returns the index of the contact that pushed the ball the hardest, if any of them did
+pub fn step_ball(ball: &mut Ball, params: &BallPhysicsParams, gravity: Vec3, manifold: &[ContactPoint], dt: f32) -> Option<usize> {
+    // predictUnconstraintMotion
+    ball.velocity *= (1. + params.drag).powf(dt);
+    let velocity = ball.velocity;
+
+    // the solver sees gravity as an external impulse
+    ball.velocity += gravity * dt;
+
+    let mut normals = Vec::with_capacity(manifold.len());
+    let mut frictions = Vec::with_capacity(manifold.len());
+
+    for contact in manifold {
+        let n = contact.normal;
+        let rel_pos = contact.point - ball.location;
+        let penetration = CONTACT_BREAKING_THRESHOLD - contact.depth;
+
+        let mut normal = SolverRow::new(ball, params, n, rel_pos);
+
+        let rel_vel = normal.velocity(ball.velocity, ball.angular_velocity);
+        let restitution = if rel_vel.abs() < RESTITUTION_VELOCITY_THRESHOLD {
+            0.
+        } else {
+            (-rel_vel * params.restitution * ARENA_RESTITUTION).max(0.)
+        };
+
+        // contacts that the ball isn't touching yet only stop it from going past them this step
+        let (positional_error, velocity_error) = if penetration > 0. {
+            (0., restitution - rel_vel - penetration / dt)
+        } else {
+            let erp = if penetration > SPLIT_IMPULSE_PENETRATION_THRESHOLD {
+                ERP
+            } else {
+                ERP2
+            };
+
+            (-penetration * erp / dt, restitution - rel_vel)
+        };
+
+        if penetration > SPLIT_IMPULSE_PENETRATION_THRESHOLD {
+            normal.rhs = (positional_error + velocity_error) * normal.jac_diag_inv;
+        } else {
+            normal.rhs = velocity_error * normal.jac_diag_inv;
+            normal.rhs_penetration = positional_error * normal.jac_diag_inv;
+        }
+
+        // friction opposes the sliding velocity, or any direction along the surface if the ball isn't sliding
+        let contact_velocity = velocity - rel_pos.cross(ball.angular_velocity);
+        let mut lateral = contact_velocity - n * contact_velocity.dot(n);
+        if lateral.dot(lateral) <= f32::EPSILON {
+            lateral = plane_space(n);
+        }
+
+        let mut friction = SolverRow::new(ball, params, lateral.normalize_or_zero(), rel_pos);
+        friction.rhs = -friction.velocity(ball.velocity, ball.angular_velocity) * friction.jac_diag_inv;
+
+        normals.push(normal);
+        frictions.push(friction);
+    }
+
+    let mut delta_linear = Vec3::default();
+    let mut delta_angular = Vec3::default();
+
+    for _ in 0..NUM_ITERATIONS {
+        for normal in &mut normals {
+            normal.solve(ball, params, 0., f32::MAX, &mut delta_linear, &mut delta_angular);
+        }
+
+        for (friction, normal) in frictions.iter_mut().zip(&normals) {
+            let limit = BALL_FRICTION * ARENA_FRICTION * normal.applied_impulse;
+            friction.solve(ball, params, -limit, limit, &mut delta_linear, &mut delta_angular);
+        }
+    }
+
+    let mut push_linear = Vec3::default();
+    let mut push_angular = Vec3::default();
+
+    for _ in 0..NUM_ITERATIONS {
+        for normal in &mut normals {
+            normal.solve_penetration(ball, params, &mut push_linear, &mut push_angular);
+        }
+    }
+
+    ball.velocity += delta_linear;
+    ball.angular_velocity += delta_angular;
+
+    // integrateTransforms, the push velocity only moves the ball this step
+    ball.location += (ball.velocity + push_linear) * dt;
+
+    normals.iter().enumerate().filter(|(_, normal)| normal.applied_impulse > 0. || normal.applied_push_impulse > 0.).max_by(|(_, a), (_, b)| (a.applied_impulse + a.applied_push_impulse).total_cmp(&(b.applied_impulse + b.applied_push_impulse))).map(|(i, _)| i)
+}
+
+// A direction that's perpendicular to `n`, Bullet's btPlaneSpace1
+fn plane_space(n: Vec3) -> Vec3 {
+    if n.z.abs() > std::f32::consts::FRAC_1_SQRT_2 {
+        Vec3::new(0., -n.z, n.y)
+    } else {
+        Vec3::new(-n.y, n.x, 0.)
+    }
+}
@@ -1,61 +1,83 @@
-use super::geometry::{Aabb, Tri};
-use super::geometry::{Ray, Sphere};
+use super::geometry::{Aabb, Surface, SurfaceKind, Tri};
+use super::geometry::{Contact, ContactPoint, Cylinder, Obb, Ray, RayHit, Sphere, SurfacePoint, SweepHit};
 use super::morton::Morton;
-use std::boxed::Box;
+use crate::error::BvhError;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use glam::Vec3;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug)]
+pub enum BvhNodeKind {
+    // index of the node's triangle in `Bvh::primitives`
+    Leaf(u32),
+    // indices of the node's children in `Bvh::nodes`
+    Branch {
+        right: u32,
+        left: u32,
+    },
+}
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug)]
 pub struct BvhNode {
-    pub is_terminal: bool,
     pub box_: Aabb,
-    pub right: Option<Box<BvhNode>>,
-    pub left: Option<Box<BvhNode>>,
-    pub primitive: Option<Tri>,
-    pub morton: Option<u64>,
+    pub kind: BvhNodeKind,
 }
 
 impl Default for BvhNode {
     fn default() -> Self {
         Self {
-            is_terminal: false,
             box_: Aabb::default(),
-            right: None,
-            left: None,
-            primitive: None,
-            morton: None,
+            kind: BvhNodeKind::Leaf(0),
         }
     }
 }
 
 impl BvhNode {
-    pub fn branch(right: Box<BvhNode>, left: Box<BvhNode>) -> Box<Self> {
-        Box::new(Self {
-            is_terminal: false,
-            box_: right.box_.add(&left.box_),
-            right: Some(right),
-            left: Some(left),
-            primitive: None,
-            morton: None,
-        })
+    pub fn branch(box_: Aabb, right: usize, left: usize) -> Self {
+        Self {
+            box_,
+            kind: BvhNodeKind::Branch {
+                right: right as u32,
+                left: left as u32,
+            },
+        }
     }
 
-    pub fn leaf(primitive: Tri, box_: Aabb, morton_code: u64) -> Box<Self> {
-        Box::new(Self {
-            is_terminal: true,
+    pub fn leaf(box_: Aabb, primitive: usize) -> Self {
+        Self {
             box_,
-            right: None,
-            left: None,
-            primitive: Some(primitive),
-            morton: Some(morton_code),
-        })
+            kind: BvhNodeKind::Leaf(primitive as u32),
+        }
+    }
+}
+
+// How the tree is split up when it's built
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BvhBuilder {
+    // splits the Morton-sorted leaves in half, fast to build
+    Morton,
+    // splits where the surface area heuristic says it's cheapest, slower to build but the boxes fit better
+    Sah,
+}
+
+impl Default for BvhBuilder {
+    fn default() -> Self {
+        BvhBuilder::Morton
     }
 }
 
 // BVH stands for "Bounding Volume Hierarchy"
+// The nodes are stored in one array, with the root first and each parent before its children
 #[derive(Clone)]
 pub struct Bvh {
     pub global_box: Aabb,
     pub num_leaves: u64,
-    pub root: Box<BvhNode>,
+    pub nodes: Vec<BvhNode>,
+    // sorted in the same order as the leaves, so neighboring leaves are next to each other in memory
+    pub primitives: Vec<Tri>,
 }
 
 fn global_aabb(boxes: &[Aabb]) -> Aabb {
@@ -68,18 +90,67 @@ fn global_aabb(boxes: &[Aabb]) -> Aabb {
     global_box
 }
 
+fn merge(a: Option<Aabb>, b: Option<Aabb>) -> Option<Aabb> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.add(&b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn component(v: Vec3, axis: usize) -> f32 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+fn sah_bin(x: f32, min: f32, max: f32) -> usize {
+    (((x - min) / (max - min) * Bvh::SAH_BINS as f32) as usize).min(Bvh::SAH_BINS - 1)
+}
+
+fn surface_kind_from_u8(kind: u8) -> Result<SurfaceKind, BvhError> {
+    [SurfaceKind::Floor, SurfaceKind::Wall, SurfaceKind::Ceiling, SurfaceKind::Ramp, SurfaceKind::Goal, SurfaceKind::Rim].into_iter().find(|&k| k as u8 == kind).ok_or_else(|| BvhError::InvalidSurface(format!("unknown surface kind {}", kind)))
+}
+
 impl Default for Bvh {
     fn default() -> Self {
         Self {
             global_box: Aabb::default(),
             num_leaves: 0,
-            root: Box::new(BvhNode::default()),
+            nodes: Vec::new(),
+            primitives: Vec::new(),
         }
     }
 }
 
 impl Bvh {
+    // the traversal stack lives on the stack, so the tree can't be deeper than this
+    const MAX_DEPTH: usize = 64;
+
+    // the number of buckets that the SAH builder sorts triangles into on each axis
+    const SAH_BINS: usize = 16;
+
+    // closest points that are this close together are treated as being equally close
+    const TIE_DISTANCE: f32 = 0.001;
+
     pub fn from(primitives: &[Tri]) -> Self {
+        Bvh::build(primitives, BvhBuilder::default())
+    }
+
+    pub fn build(primitives: &[Tri], builder: BvhBuilder) -> Self {
+        match builder {
+            BvhBuilder::Morton => Bvh::build_morton(primitives),
+            BvhBuilder::Sah => Bvh::build_sah(primitives),
+        }
+    }
+
+    // Builds a new tree out of the same triangles, like `game.arena.collision_mesh.rebuild(BvhBuilder::Sah)`
+    pub fn rebuild(&self, builder: BvhBuilder) -> Self {
+        Bvh::build(&self.primitives, builder)
+    }
+
+    fn build_morton(primitives: &[Tri]) -> Self {
         let num_leaves = primitives.len();
 
         let mut boxes: Vec<Aabb> = Vec::with_capacity(num_leaves);
@@ -90,132 +161,855 @@ impl Bvh {
         let global_box = global_aabb(&boxes);
 
         let morton = Morton::from(&global_box);
-        let mut sorted_leaves: Vec<Box<BvhNode>> = Vec::with_capacity(num_leaves);
+        let mut sorted_leaves: Vec<(u64, usize)> = boxes.iter().enumerate().map(|(i, box_)| (morton.get_code(box_), i)).collect();
 
-        for (i, box_) in boxes.iter().enumerate() {
-            sorted_leaves.push(BvhNode::leaf(primitives[i], *box_, morton.get_code(box_)));
-        }
+        sorted_leaves.sort_unstable_by_key(|leaf| leaf.0);
 
-        sorted_leaves.sort_unstable_by_key(|leaf| leaf.morton);
+        let sorted_boxes: Vec<Aabb> = sorted_leaves.iter().map(|&(_, i)| boxes[i]).collect();
+        let sorted_primitives: Vec<Tri> = sorted_leaves.iter().map(|&(_, i)| primitives[i].clone()).collect();
 
-        let root = Bvh::generate_hierarchy(&sorted_leaves, 0, num_leaves - 1);
+        let mut nodes = Vec::with_capacity(2 * num_leaves - 1);
+        Bvh::generate_hierarchy(&sorted_boxes, 0, num_leaves - 1, &mut nodes);
 
         Self {
             global_box,
             num_leaves: num_leaves as u64,
-            root,
+            nodes,
+            primitives: sorted_primitives,
         }
     }
 
-    fn generate_hierarchy(sorted_leaves: &[Box<BvhNode>], first: usize, last: usize) -> Box<BvhNode> {
+    // Adds the nodes for the leaves from first to last, returning the index of the subtree's root
+    fn generate_hierarchy(sorted_boxes: &[Aabb], first: usize, last: usize, nodes: &mut Vec<BvhNode>) -> usize {
         // If we're dealing with a single object, return the leaf node
         if first == last {
-            return sorted_leaves[first].clone();
+            nodes.push(BvhNode::leaf(sorted_boxes[first], first));
+            return nodes.len() - 1;
         }
 
+        // the parent goes before its children, it's filled in once they're done
+        let index = nodes.len();
+        nodes.push(BvhNode::default());
+
         // Determine where to split the range
 
         let split = first + ((last - first) / 2);
 
         // Process the resulting sub-ranges recursively
 
-        let right = Bvh::generate_hierarchy(sorted_leaves, first, split);
-        let left = Bvh::generate_hierarchy(sorted_leaves, split + 1, last);
+        let right = Bvh::generate_hierarchy(sorted_boxes, first, split, nodes);
+        let left = Bvh::generate_hierarchy(sorted_boxes, split + 1, last, nodes);
+
+        nodes[index] = BvhNode::branch(nodes[right].box_.add(&nodes[left].box_), right, left);
 
-        BvhNode::branch(right, left)
+        index
     }
 
-    pub fn intersect(&self, query_object: &Sphere) -> Vec<Tri> {
-        let query_box: Aabb = Aabb::from_sphere(query_object);
+    fn build_sah(primitives: &[Tri]) -> Self {
+        let num_leaves = primitives.len();
 
-        let mut hits = Vec::with_capacity(16);
+        let boxes: Vec<Aabb> = primitives.iter().map(Aabb::from_tri).collect();
+        let centroids: Vec<Vec3> = boxes.iter().map(|box_| (box_.min + box_.max) / 2.).collect();
 
-        // Allocate traversal stack from thread-local memory,
-        // and push NULL to indicate that there are no postponed nodes.
-        let mut stack: Vec<&BvhNode> = Vec::with_capacity(32);
-
-        // Traverse nodes starting from the root.
-        let mut node = &*self.root;
-        // Check each child node for overlap.
-        loop {
-            // We must save the right node to a variable
-            // There's the potential for node to be overwritten
-            let right_og = node.right.as_deref();
-
-            let mut traverse_left = false;
-            if let Some(left) = node.left.as_deref() {
-                if left.box_.intersect_self(&query_box) {
-                    match left.primitive {
-                        Some(left_tri) => {
-                            if left_tri.intersect_sphere(query_object) {
-                                hits.push(left_tri);
-                            }
-                        }
-                        None => {
-                            traverse_left = true;
-                            node = left;
-                        }
-                    }
+        let mut order: Vec<usize> = (0..num_leaves).collect();
+        let mut nodes = Vec::with_capacity(2 * num_leaves - 1);
+        Bvh::generate_sah_hierarchy(&boxes, &centroids, &mut order, 0, 0, &mut nodes);
+
+        Self {
+            global_box: nodes[0].box_,
+            num_leaves: num_leaves as u64,
+            nodes,
+            primitives: order.iter().map(|&i| primitives[i].clone()).collect(),
+        }
+    }
+
+    // Adds the nodes for the leaves in `order`, which starts at `offset` in the final list of primitives
+    // `order` is rearranged so that the leaves of each subtree are next to each other
+    fn generate_sah_hierarchy(boxes: &[Aabb], centroids: &[Vec3], order: &mut [usize], offset: usize, depth: usize, nodes: &mut Vec<BvhNode>) -> usize {
+        if order.len() == 1 {
+            nodes.push(BvhNode::leaf(boxes[order[0]], offset));
+            return nodes.len() - 1;
+        }
+
+        let index = nodes.len();
+        nodes.push(BvhNode::default());
+
+        // fall back to splitting in half so that the tree can't get too deep to traverse
+        let split = if depth + 32 < Bvh::MAX_DEPTH {
+            Bvh::sah_split(boxes, centroids, order)
+        } else {
+            None
+        }
+        .unwrap_or(order.len() / 2);
+
+        let (right_order, left_order) = order.split_at_mut(split);
+        let right = Bvh::generate_sah_hierarchy(boxes, centroids, right_order, offset, depth + 1, nodes);
+        let left = Bvh::generate_sah_hierarchy(boxes, centroids, left_order, offset + split, depth + 1, nodes);
+
+        nodes[index] = BvhNode::branch(nodes[right].box_.add(&nodes[left].box_), right, left);
+
+        index
+    }
+
+    // Finds the cheapest place to split the leaves, and sorts them into the two halves
+    // Returns the number of leaves in the first half, or None if their centers are all in the same place
+    fn sah_split(boxes: &[Aabb], centroids: &[Vec3], order: &mut [usize]) -> Option<usize> {
+        // the cost, the axis, and the last bin of the first half
+        let mut best: Option<(f32, usize, usize)> = None;
+        let mut bounds = [(0., 0.); 3];
+
+        for (a, bound) in bounds.iter_mut().enumerate() {
+            let min = order.iter().map(|&i| component(centroids[i], a)).fold(f32::MAX, f32::min);
+            let max = order.iter().map(|&i| component(centroids[i], a)).fold(f32::MIN, f32::max);
+            *bound = (min, max);
+
+            if max <= min {
+                continue;
+            }
+
+            let mut bin_boxes: [Option<Aabb>; Bvh::SAH_BINS] = [None; Bvh::SAH_BINS];
+            let mut bin_counts = [0; Bvh::SAH_BINS];
+
+            for &i in order.iter() {
+                let b = sah_bin(component(centroids[i], a), min, max);
+                bin_counts[b] += 1;
+                bin_boxes[b] = merge(bin_boxes[b], Some(boxes[i]));
+            }
+
+            // the cost of everything after each bin
+            let mut right_costs = [0.; Bvh::SAH_BINS];
+            let mut right_box = None;
+            let mut right_count = 0;
+
+            for b in (1..Bvh::SAH_BINS).rev() {
+                right_box = merge(right_box, bin_boxes[b]);
+                right_count += bin_counts[b];
+                right_costs[b - 1] = right_box.map_or(0., |box_: Aabb| box_.surface_area()) * right_count as f32;
+            }
+
+            let mut left_box = None;
+            let mut left_count = 0;
+
+            for b in 0..Bvh::SAH_BINS - 1 {
+                left_box = merge(left_box, bin_boxes[b]);
+                left_count += bin_counts[b];
+
+                if left_count == 0 || left_count == order.len() {
+                    continue;
                 }
+
+                let cost = left_box.map_or(0., |box_: Aabb| box_.surface_area()) * left_count as f32 + right_costs[b];
+
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, a, b));
+                }
+            }
+        }
+
+        let (_, a, last_bin) = best?;
+        let (min, max) = bounds[a];
+
+        // move the first half to the front
+        let mut split = 0;
+        for i in 0..order.len() {
+            if sah_bin(component(centroids[order[i]], a), min, max) <= last_bin {
+                order.swap(i, split);
+                split += 1;
+            }
+        }
+
+        Some(split)
+    }
+
+    const MAGIC: &'static [u8; 5] = b"RLBVH";
+    const VERSION: u8 = 1;
+    // longer than any of the arenas' surface names, so a bad length can't make a huge allocation
+    const MAX_NAME_LEN: usize = 64;
+    // marks a leaf in the binary format, branches store their child indices instead
+    const LEAF: u32 = u32::MAX;
+
+    // Writes the tree in a compact little-endian binary format
+    // Only the triangles and the shape of the tree are stored, the boxes are recalculated when it's read
+    // Fails without writing anything if a surface name is longer than `from_bin` accepts
+    pub fn to_bin(&self, writer: impl Write) -> io::Result<()> {
+        let mut names: Vec<&str> = Vec::new();
+        for tri in &self.primitives {
+            if !names.contains(&&*tri.surface.name) {
+                if tri.surface.name.len() > Bvh::MAX_NAME_LEN {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("surface name {:?} is longer than {} bytes", tri.surface.name, Bvh::MAX_NAME_LEN)));
+                }
+
+                names.push(&tri.surface.name);
+            }
+        }
+
+        let mut writer = BufWriter::new(writer);
+
+        writer.write_all(Bvh::MAGIC)?;
+        writer.write_u8(Bvh::VERSION)?;
+
+        writer.write_u32::<LittleEndian>(names.len() as u32)?;
+        for name in &names {
+            writer.write_u32::<LittleEndian>(name.len() as u32)?;
+            writer.write_all(name.as_bytes())?;
+        }
+
+        writer.write_u32::<LittleEndian>(self.primitives.len() as u32)?;
+        for tri in &self.primitives {
+            for p in &tri.p {
+                writer.write_f32::<LittleEndian>(p.x)?;
+                writer.write_f32::<LittleEndian>(p.y)?;
+                writer.write_f32::<LittleEndian>(p.z)?;
             }
 
-            let mut traverse_right = false;
-            if let Some(right) = right_og {
-                if right.box_.intersect_self(&query_box) {
-                    match right.primitive {
-                        Some(right_tri) => {
-                            if right_tri.intersect_sphere(query_object) {
-                                hits.push(right_tri);
-                            }
+            writer.write_u8(tri.surface.kind as u8)?;
+            writer.write_u32::<LittleEndian>(names.iter().position(|&name| name == &*tri.surface.name).unwrap_or_default() as u32)?;
+        }
+
+        writer.write_u32::<LittleEndian>(self.nodes.len() as u32)?;
+        for node in &self.nodes {
+            let (a, b) = match node.kind {
+                BvhNodeKind::Leaf(primitive) => (Bvh::LEAF, primitive),
+                BvhNodeKind::Branch {
+                    right,
+                    left,
+                } => (right, left),
+            };
+
+            writer.write_u32::<LittleEndian>(a)?;
+            writer.write_u32::<LittleEndian>(b)?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn to_bin_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.to_bin(File::create(path)?)
+    }
+
+    // Reads a tree that was written by `to_bin`
+    pub fn from_bin(mut reader: impl Read) -> Result<Self, BvhError> {
+        let mut magic = [0; 5];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != Bvh::MAGIC {
+            return Err(BvhError::NotABvh);
+        }
+
+        let version = reader.read_u8()?;
+        if version != Bvh::VERSION {
+            return Err(BvhError::UnsupportedVersion(version));
+        }
+
+        let num_names = reader.read_u32::<LittleEndian>()? as usize;
+        let mut names: Vec<Arc<str>> = Vec::new();
+        for _ in 0..num_names {
+            let len = reader.read_u32::<LittleEndian>()? as usize;
+            if len > Bvh::MAX_NAME_LEN {
+                return Err(BvhError::InvalidSurface(format!("surface name is {} bytes long", len)));
+            }
+
+            let mut bytes = [0; Bvh::MAX_NAME_LEN];
+            reader.read_exact(&mut bytes[..len])?;
+
+            let name = std::str::from_utf8(&bytes[..len]).map_err(|_| BvhError::InvalidSurface(format!("surface name {:?} isn't UTF-8", String::from_utf8_lossy(&bytes[..len]))))?;
+            names.push(Arc::from(name));
+        }
+
+        let num_primitives = reader.read_u32::<LittleEndian>()? as usize;
+        let mut primitives = Vec::new();
+        for _ in 0..num_primitives {
+            let mut tri = Tri::default();
+
+            for p in &mut tri.p {
+                *p = Vec3::new(reader.read_f32::<LittleEndian>()?, reader.read_f32::<LittleEndian>()?, reader.read_f32::<LittleEndian>()?);
+            }
+
+            let kind = surface_kind_from_u8(reader.read_u8()?)?;
+            let name = reader.read_u32::<LittleEndian>()? as usize;
+
+            tri.surface = Surface {
+                kind,
+                name: names.get(name).cloned().ok_or(BvhError::IndexOutOfBounds {
+                    what: "surface name",
+                    index: name,
+                    len: num_names,
+                })?,
+            };
+
+            primitives.push(tri);
+        }
+
+        let num_nodes = reader.read_u32::<LittleEndian>()? as usize;
+        if num_nodes == 0 {
+            return Err(BvhError::Empty);
+        }
+
+        let mut nodes = Vec::new();
+        for _ in 0..num_nodes {
+            let a = reader.read_u32::<LittleEndian>()?;
+            let b = reader.read_u32::<LittleEndian>()?;
+
+            nodes.push(if a == Bvh::LEAF {
+                BvhNode {
+                    box_: Aabb::default(),
+                    kind: BvhNodeKind::Leaf(b),
+                }
+            } else {
+                BvhNode {
+                    box_: Aabb::default(),
+                    kind: BvhNodeKind::Branch {
+                        right: a,
+                        left: b,
+                    },
+                }
+            });
+        }
+
+        Bvh::validate_tree(&nodes, num_primitives)?;
+
+        // children come after their parents, so going backwards fills in every child's box before its parent's
+        let mut num_leaves = 0;
+        for i in (0..num_nodes).rev() {
+            nodes[i].box_ = match nodes[i].kind {
+                BvhNodeKind::Leaf(primitive) => {
+                    num_leaves += 1;
+                    Aabb::from_tri(&primitives[primitive as usize])
+                }
+                BvhNodeKind::Branch {
+                    right,
+                    left,
+                } => nodes[right as usize].box_.add(&nodes[left as usize].box_),
+            };
+        }
+
+        Ok(Self {
+            global_box: nodes[0].box_,
+            num_leaves,
+            nodes,
+            primitives,
+        })
+    }
+
+    pub fn from_bin_file(path: impl AsRef<Path>) -> Result<Self, BvhError> {
+        Bvh::from_bin(BufReader::new(File::open(path)?))
+    }
+
+    // Checks that every node but the root has exactly one parent that comes before it,
+    // and that the tree isn't too deep for the traversal stack
+    fn validate_tree(nodes: &[BvhNode], num_primitives: usize) -> Result<(), BvhError> {
+        let mut depths: Vec<Option<usize>> = vec![None; nodes.len()];
+        depths[0] = Some(0);
+
+        for (i, node) in nodes.iter().enumerate() {
+            let depth = depths[i].ok_or(BvhError::InvalidTree)?;
+
+            match node.kind {
+                BvhNodeKind::Leaf(primitive) => {
+                    if primitive as usize >= num_primitives {
+                        return Err(BvhError::IndexOutOfBounds {
+                            what: "triangle",
+                            index: primitive as usize,
+                            len: num_primitives,
+                        });
+                    }
+                }
+                BvhNodeKind::Branch {
+                    right,
+                    left,
+                } => {
+                    for child in [right as usize, left as usize] {
+                        if child >= nodes.len() {
+                            return Err(BvhError::IndexOutOfBounds {
+                                what: "node",
+                                index: child,
+                                len: nodes.len(),
+                            });
                         }
-                        None => {
-                            traverse_right = true;
-
-                            if traverse_left {
-                                stack.push(right);
-                            } else {
-                                node = right;
-                            }
+
+                        if child <= i || depths[child].is_some() || depth + 2 > Bvh::MAX_DEPTH {
+                            return Err(BvhError::InvalidTree);
                         }
+
+                        depths[child] = Some(depth + 1);
                     }
                 }
             }
+        }
 
-            if !(traverse_left || traverse_right) {
-                match stack.pop() {
-                    Some(n) => node = n,
-                    None => break,
+        Ok(())
+    }
+
+    // Calls `f` with every triangle that intersects the sphere, without allocating
+    pub fn for_each_intersecting(&self, query_object: &Sphere, mut f: impl FnMut(&Tri)) {
+        self.for_each_overlapping(&Aabb::from_sphere(query_object), |tri| {
+            if tri.intersect_sphere(query_object) {
+                f(tri);
+            }
+        });
+    }
+
+    // Calls `f` with every triangle whose box overlaps the query box
+    fn for_each_overlapping(&self, query_box: &Aabb, mut f: impl FnMut(&Tri)) {
+        if self.nodes.is_empty() || !self.nodes[0].box_.intersect_self(query_box) {
+            return;
+        }
+
+        // only nodes whose boxes overlap the query box are pushed
+        let mut stack = [0u32; Bvh::MAX_DEPTH];
+        let mut stack_len = 1;
+
+        while stack_len > 0 {
+            stack_len -= 1;
+
+            match self.nodes[stack[stack_len] as usize].kind {
+                // a leaf's box is its triangle's box, and it was checked before the leaf was pushed
+                BvhNodeKind::Leaf(primitive) => f(&self.primitives[primitive as usize]),
+                BvhNodeKind::Branch {
+                    right,
+                    left,
+                } => {
+                    for child in [right, left] {
+                        if self.nodes[child as usize].box_.intersect_self(query_box) {
+                            stack[stack_len] = child;
+                            stack_len += 1;
+                        }
+                    }
                 }
             }
         }
+    }
+
+    // The surface area heuristic's estimate of how many nodes a query visits,
+    // the chance that a random query hits a node is taken to be proportional to the node's surface area
+    // Lower is better, use it to compare trees built from the same triangles
+    pub fn expected_node_visits(&self) -> f32 {
+        if self.nodes.is_empty() {
+            return 0.;
+        }
+
+        let root_area = self.nodes[0].box_.surface_area();
+
+        self.nodes.iter().map(|node| node.box_.surface_area() / root_area).sum()
+    }
+
+    #[deprecated(note = "allocates a Vec for every query, use for_each_intersecting instead")]
+    pub fn intersect(&self, query_object: &Sphere) -> Vec<Tri> {
+        let mut hits = Vec::with_capacity(16);
+
+        self.for_each_intersecting(query_object, |tri| hits.push(tri.clone()));
 
         hits
     }
 
-    pub fn collide(&self, s: &Sphere) -> Option<Ray> {
-        let mut contact_point = Ray::default();
-        let mut count = 0;
+    pub fn collide(&self, s: &Sphere) -> Option<Contact> {
+        self.collide_with(s, |_, _| true)
+    }
 
-        let tris_hit = self.intersect(s);
+    // Like `collide`, but only the triangles where `keep(tri, contact_point)` is true are collided with
+    pub fn collide_with(&self, s: &Sphere, keep: impl Fn(&Tri, Vec3) -> bool) -> Option<Contact> {
+        let mut contact_point = Contact::default();
+        let mut count = 0;
+        let mut max_penetration = f32::MIN;
 
-        for tri in tris_hit {
+        self.for_each_intersecting(s, |tri| {
             let p = tri.center();
             let n = tri.unit_normal();
 
-            let separation = (s.center - p).dot(&n);
-            if separation <= s.radius {
+            let separation = (s.center - p).dot(n);
+            if separation <= s.radius && keep(tri, s.center - n * separation) {
                 count += 1;
                 contact_point.start += s.center - n * separation;
                 contact_point.direction += n * (s.radius - separation);
+
+                // report the surface that the sphere is the deepest into
+                if s.radius - separation > max_penetration {
+                    max_penetration = s.radius - separation;
+                    contact_point.surface = tri.surface.clone();
+                }
+            }
+        });
+
+        if count == 0 {
+            return None;
+        }
+
+        contact_point.start /= count as f32;
+        contact_point.direction = contact_point.direction.normalize_or_zero();
+
+        Some(contact_point)
+    }
+
+    // Moves the sphere along `d` and finds the first triangle that it touches, see `Tri::sweep_sphere`
+    pub fn sweep_sphere(&self, s: &Sphere, d: Vec3) -> Option<SweepHit> {
+        self.sweep_sphere_with(s, d, |_, _| true)
+    }
+
+    // Like `sweep_sphere`, but only the triangles where `keep(tri, hit_point)` is true can be hit
+    pub fn sweep_sphere_with(&self, s: &Sphere, d: Vec3, keep: impl Fn(&Tri, Vec3) -> bool) -> Option<SweepHit> {
+        let start = Aabb::from_sphere(s);
+        let end = Aabb::from_sphere(&Sphere {
+            center: s.center + d,
+            radius: s.radius,
+        });
+
+        let mut first_hit: Option<SweepHit> = None;
+
+        self.for_each_overlapping(&start.add(&end), |tri| {
+            if let Some(hit) = tri.sweep_sphere(s, d) {
+                if first_hit.as_ref().is_none_or(|first_hit| hit.toi < first_hit.toi) && keep(tri, hit.point) {
+                    first_hit = Some(hit);
+                }
+            }
+        });
+
+        first_hit
+    }
+
+    // The closest triangle that the ray hits, rays end at start + direction
+    pub fn raycast(&self, ray: &Ray) -> Option<RayHit> {
+        let mut closest: Option<(f32, &Tri)> = None;
+
+        self.for_each_on_ray(ray, |tri, max_t| {
+            if let Some(t) = tri.raycast(ray).filter(|&t| t < max_t) {
+                closest = Some((t, tri));
+                return Some(t);
+            }
+
+            None
+        });
+
+        let (t, tri) = closest?;
+        let normal = tri.unit_normal();
+
+        Some(RayHit {
+            distance: t * ray.direction.length(),
+            point: ray.start + ray.direction * t,
+            normal: if normal.dot(ray.direction) > 0. {
+                normal * -1.
+            } else {
+                normal
+            },
+            tri: tri.clone(),
+        })
+    }
+
+    // Whether the ray hits anything at all, faster than `raycast` because it stops at the first hit
+    pub fn raycast_any(&self, ray: &Ray) -> bool {
+        let mut hit = false;
+
+        self.for_each_on_ray(ray, |tri, _| {
+            hit = tri.raycast(ray).is_some();
+            hit.then_some(-1.)
+        });
+
+        hit
+    }
+
+    // Calls `f` with the triangles whose boxes the ray goes through, closest boxes first
+    // `f` returns the new furthest fraction of the ray to search if it shortened it, a negative fraction stops the search
+    fn for_each_on_ray<'a>(&'a self, ray: &Ray, mut f: impl FnMut(&'a Tri, f32) -> Option<f32>) {
+        let mut max_t = 1.;
+
+        if self.nodes.is_empty() || self.nodes[0].box_.intersect_ray(ray, max_t).is_none() {
+            return;
+        }
+
+        // each node is pushed with the fraction of the ray where it enters the node's box
+        let mut stack = [(0u32, 0.); Bvh::MAX_DEPTH];
+        let mut stack_len = 1;
+
+        while stack_len > 0 {
+            stack_len -= 1;
+            let (node, t) = stack[stack_len];
+
+            // something closer than this box was already hit
+            if t > max_t {
+                continue;
+            }
+
+            match self.nodes[node as usize].kind {
+                BvhNodeKind::Leaf(primitive) => {
+                    if let Some(t) = f(&self.primitives[primitive as usize], max_t) {
+                        max_t = t;
+                    }
+                }
+                BvhNodeKind::Branch {
+                    right,
+                    left,
+                } => {
+                    let right_t = self.nodes[right as usize].box_.intersect_ray(ray, max_t);
+                    let left_t = self.nodes[left as usize].box_.intersect_ray(ray, max_t);
+
+                    // the closer child is pushed last so it's searched first
+                    let mut children = [(right, right_t), (left, left_t)];
+                    if right_t < left_t {
+                        children.swap(0, 1);
+                    }
+
+                    for (child, t) in children {
+                        if let Some(t) = t {
+                            stack[stack_len] = (child, t);
+                            stack_len += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // The closest point on the arena to `p`, if there is one within `max_distance`
+    // A smaller `max_distance` skips more of the tree
+    pub fn closest_point(&self, p: Vec3, max_distance: f32) -> Option<SurfacePoint> {
+        if self.nodes.is_empty() || max_distance < 0. {
+            return None;
+        }
+
+        // (distance, how well the triangle's normal lines up with the direction to `p`, triangle, closest point)
+        let mut closest: Option<(f32, f32, &Tri, Vec3)> = None;
+        let mut max_distance_squared = max_distance * max_distance;
+
+        // each node is pushed with the squared distance to its box
+        let mut stack = [(0u32, self.nodes[0].box_.distance_squared(p)); Bvh::MAX_DEPTH];
+        let mut stack_len = 1;
+
+        while stack_len > 0 {
+            stack_len -= 1;
+            let (node, distance_squared) = stack[stack_len];
+
+            if distance_squared > max_distance_squared {
+                continue;
+            }
+
+            match self.nodes[node as usize].kind {
+                BvhNodeKind::Leaf(primitive) => {
+                    let tri = &self.primitives[primitive as usize];
+                    let point = tri.closest_point(p);
+                    let distance = (p - point).length();
+
+                    if distance > max_distance_squared.sqrt() {
+                        continue;
+                    }
+
+                    // at an edge or a corner, more than one triangle is the closest
+                    // the one that faces `p` the most decides which side of the arena `p` is on
+                    let alignment = if distance > 0. {
+                        ((p - point) / distance).dot(tri.unit_normal()).abs()
+                    } else {
+                        1.
+                    };
+
+                    let is_closer = match closest {
+                        Some((closest_distance, closest_alignment, _, _)) => distance < closest_distance - Bvh::TIE_DISTANCE || (distance <= closest_distance + Bvh::TIE_DISTANCE && alignment > closest_alignment),
+                        None => true,
+                    };
+
+                    if is_closer {
+                        closest = Some((distance, alignment, tri, point));
+                        max_distance_squared = (distance + Bvh::TIE_DISTANCE).powi(2);
+                    }
+                }
+                BvhNodeKind::Branch {
+                    right,
+                    left,
+                } => {
+                    let right_distance = self.nodes[right as usize].box_.distance_squared(p);
+                    let left_distance = self.nodes[left as usize].box_.distance_squared(p);
+
+                    // the closer child is pushed last so it's searched first
+                    let mut children = [(right, right_distance), (left, left_distance)];
+                    if right_distance < left_distance {
+                        children.swap(0, 1);
+                    }
+
+                    for (child, distance_squared) in children {
+                        if distance_squared <= max_distance_squared {
+                            stack[stack_len] = (child, distance_squared);
+                            stack_len += 1;
+                        }
+                    }
+                }
             }
         }
 
+        closest.map(|(distance, _, tri, point)| SurfacePoint {
+            point,
+            normal: tri.unit_normal(),
+            distance,
+            surface: tri.surface.clone(),
+        })
+    }
+
+    // The distance from `p` to the arena, negative if `p` is behind the closest triangle (outside of the arena)
+    // Points further than `max_distance` from the arena are reported as `max_distance` away
+    pub fn signed_distance(&self, p: Vec3, max_distance: f32) -> f32 {
+        match self.closest_point(p, max_distance) {
+            Some(closest) if (p - closest.point).dot(closest.normal) < 0. => -closest.distance,
+            Some(closest) => closest.distance,
+            None => max_distance,
+        }
+    }
+
+    // Every triangle that overlaps the box
+    pub fn intersect_obb(&self, obb: &Obb) -> Vec<Tri> {
+        let mut hits = Vec::with_capacity(16);
+
+        self.for_each_overlapping(&Aabb::from_obb(obb), |tri| {
+            if obb.intersect_tri(tri) {
+                hits.push(tri.clone());
+            }
+        });
+
+        hits
+    }
+
+    // The contact manifold between the box and the arena
+    // Each corner of the box that is past a triangle it overlaps, and lines up with the inside of the triangle, is a contact
+    // A triangle that the box overlaps without any of its corners, like an edge under the box, gives one contact at the box's deepest point
+    pub fn collide_obb(&self, obb: &Obb) -> Vec<ContactPoint> {
+        let corners = obb.corners();
+        let mut manifold: Vec<ContactPoint> = Vec::new();
+
+        self.for_each_overlapping(&Aabb::from_obb(obb), |tri| {
+            if !obb.intersect_tri(tri) {
+                return;
+            }
+
+            let n = tri.unit_normal();
+            let mut found = false;
+
+            for corner in corners {
+                let depth = (tri.p[0] - corner).dot(n);
+                let point = corner + n * depth;
+
+                if depth >= 0. && tri.contains(point) {
+                    found = true;
+
+                    // corners over the seam between two triangles are only counted once
+                    if !manifold.iter().any(|contact| (contact.point - point).length() < 0.01) {
+                        manifold.push(ContactPoint {
+                            point,
+                            normal: n,
+                            depth,
+                            surface: tri.surface.clone(),
+                        });
+                    }
+                }
+            }
+
+            if !found {
+                let deepest = corners.into_iter().max_by(|a, b| (tri.p[0] - *a).dot(n).total_cmp(&(tri.p[0] - *b).dot(n))).unwrap_or_default();
+                let depth = (tri.p[0] - deepest).dot(n);
+
+                if depth >= 0. {
+                    manifold.push(ContactPoint {
+                        point: tri.closest_point(deepest),
+                        normal: n,
+                        depth,
+                        surface: tri.surface.clone(),
+                    });
+                }
+            }
+        });
+
+        manifold
+    }
+
+    // The contact manifold between the sphere and the arena, deepest contact first
+    // Unlike `collide`, each triangle touches the sphere at the triangle's closest point to the sphere's center,
+    // so the normals of edges and corners point at the center and nothing gets averaged
+    pub fn collide_manifold(&self, s: &Sphere) -> Vec<ContactPoint> {
+        self.collide_manifold_with(s, |_, _| true)
+    }
+
+    // Like `collide_manifold`, but only the triangles where `keep(tri, contact_point)` is true are collided with
+    pub fn collide_manifold_with(&self, s: &Sphere, keep: impl Fn(&Tri, Vec3) -> bool) -> Vec<ContactPoint> {
+        // each contact, with the normal of the triangle that it's on
+        let mut contacts: Vec<(ContactPoint, Vec3)> = Vec::new();
+
+        self.for_each_intersecting(s, |tri| {
+            let n = tri.unit_normal();
+            let point = tri.closest_point(s.center);
+            let separation = (s.center - tri.p[0]).dot(n);
+
+            let (normal, distance) = if separation > 0. {
+                let d = s.center - point;
+                (d.normalize_or_zero(), d.length())
+            } else if tri.contains(s.center - n * separation) {
+                // the center went through the face, push it straight back out
+                (n, separation)
+            } else {
+                // the center is behind the triangle and off to the side of it, the triangle's neighbors handle this
+                return;
+            };
+
+            let depth = s.radius - distance;
+
+            // triangles that share an edge or a corner touch the sphere at the same point
+            if depth < 0. || !keep(tri, point) || contacts.iter().any(|(contact, _)| (contact.point - point).length() < 0.01) {
+                return;
+            }
+
+            contacts.push((
+                ContactPoint {
+                    point,
+                    normal,
+                    depth,
+                    surface: tri.surface.clone(),
+                },
+                n,
+            ));
+        });
+
+        // on a flat surface that's made of more than one triangle, the edges of the triangles next to the one under the sphere
+        // are only seams, so contacts on the edges of triangles that are in the same plane as a face contact are dropped
+        let is_face = |(contact, n): &(ContactPoint, Vec3)| (contact.normal - *n).length() < 0.001;
+        let faces: Vec<Vec3> = contacts.iter().filter(|contact| is_face(contact)).map(|(_, n)| *n).collect();
+        contacts.retain(|contact| is_face(contact) || !faces.iter().any(|face| (*face - contact.1).length() < 0.001));
+
+        let mut manifold: Vec<ContactPoint> = contacts.into_iter().map(|(contact, _)| contact).collect();
+        manifold.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+
+        manifold
+    }
+
+    // Like `collide`, but each triangle pushes on the point of the cylinder that is the deepest past its plane
+    // The contact's start is on the surface of the arena
+    pub fn collide_cylinder(&self, c: &Cylinder) -> Option<Contact> {
+        let mut contact_point = Contact::default();
+        let mut count = 0;
+        let mut max_penetration = f32::MIN;
+
+        self.for_each_intersecting(&c.bounding_sphere(), |tri| {
+            let n = tri.unit_normal();
+
+            // ignore triangles that the cylinder is already behind
+            if (c.center - tri.p[0]).dot(n) <= 0. {
+                return;
+            }
+
+            let deepest = c.support(n * -1.);
+            let penetration = (tri.p[0] - deepest).dot(n);
+            let p = deepest + n * penetration;
+
+            if penetration >= 0. && tri.contains(p) {
+                count += 1;
+                contact_point.start += p;
+                contact_point.direction += n * penetration.max(0.001);
+
+                if penetration > max_penetration {
+                    max_penetration = penetration;
+                    contact_point.surface = tri.surface.clone();
+                }
+            }
+        });
+
         if count == 0 {
             return None;
         }
 
         contact_point.start /= count as f32;
-        contact_point.direction = contact_point.direction.normalize();
+        contact_point.direction = contact_point.direction.normalize_or_zero();
 
         Some(contact_point)
     }
@@ -223,8 +1017,10 @@ impl Bvh {
 
 #[cfg(test)]
 mod test {
+    use crate::linear_algebra::mat::Mat3;
+    use crate::simulation::mesh::Mesh;
     use criterion::black_box;
-    use vvec3::Vec3;
+    use glam::Vec3;
 
     use super::*;
 
@@ -235,6 +1031,12 @@ mod test {
     const MAX_Y: f32 = 6000.0;
     const MAX_Z: f32 = 2075.45;
 
+    fn count_intersecting(bvh: &Bvh, sphere: &Sphere) -> usize {
+        let mut count = 0;
+        bvh.for_each_intersecting(sphere, |_| count += 1);
+        count
+    }
+
     #[test]
     fn global_bounding_box() {
         let bounding_boxes = vec![
@@ -328,6 +1130,7 @@ mod test {
                 let p = [verts[map[0]], verts[map[1]], verts[map[2]]];
                 Tri {
                     p,
+                    ..Default::default()
                 }
             })
             .collect()
@@ -341,6 +1144,80 @@ mod test {
     }
 
     #[test]
+    fn test_bvh_layout() {
+        let triangles = generate_tris();
+
+        let bvh = Bvh::from(&triangles);
+
+        assert_eq!(bvh.nodes.len(), 2 * triangles.len() - 1);
+        assert_eq!(bvh.primitives.len(), triangles.len());
+
+        // every parent comes before its children, and contains their boxes
+        for (i, node) in bvh.nodes.iter().enumerate() {
+            if let BvhNodeKind::Branch {
+                right,
+                left,
+            } = node.kind
+            {
+                for child in [right as usize, left as usize] {
+                    assert!(child > i);
+                    let box_ = node.box_.add(&bvh.nodes[child].box_);
+                    assert!((box_.min - node.box_.min).length() < f32::EPSILON);
+                    assert!((box_.max - node.box_.max).length() < f32::EPSILON);
+                }
+            }
+        }
+
+        // a tree with only one triangle is just a leaf
+        let bvh = Bvh::from(&triangles[4..5]);
+        let sphere = Sphere {
+            center: Vec3::new(-4000., -5000., 0.),
+            radius: 100.,
+        };
+        assert_eq!(count_intersecting(&bvh, &sphere), 1);
+
+        assert!(Bvh::default().collide(&sphere).is_none());
+    }
+
+    #[test]
+    fn test_bvh_sah() {
+        let triangles = generate_tris();
+
+        let morton = Bvh::build(&triangles, BvhBuilder::Morton);
+        let sah = Bvh::build(&triangles, BvhBuilder::Sah);
+
+        assert_eq!(sah.nodes.len(), 2 * triangles.len() - 1);
+        assert_eq!(sah.num_leaves, morton.num_leaves);
+        assert!((sah.global_box.min - morton.global_box.min).length() < f32::EPSILON);
+        assert!((sah.global_box.max - morton.global_box.max).length() < f32::EPSILON);
+        assert!(sah.expected_node_visits() <= morton.expected_node_visits());
+
+        // every triangle is in exactly one leaf
+        let mut leaves: Vec<u32> = sah
+            .nodes
+            .iter()
+            .filter_map(|node| match node.kind {
+                BvhNodeKind::Leaf(primitive) => Some(primitive),
+                BvhNodeKind::Branch {
+                    ..
+                } => None,
+            })
+            .collect();
+        leaves.sort_unstable();
+        assert_eq!(leaves, (0..triangles.len() as u32).collect::<Vec<_>>());
+
+        for center in [Vec3::new(0., 0., 0.), Vec3::new(4096., 5120., 0.), Vec3::new(4096. / 2., 5120. / 2., 100.), Vec3::new(0., 0., 1022.)] {
+            let sphere = Sphere {
+                center,
+                radius: 100.,
+            };
+
+            assert_eq!(count_intersecting(&sah, &sphere), count_intersecting(&morton, &sphere));
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
     fn test_bvh_intersect() {
         let triangles = generate_tris();
 
@@ -495,4 +1372,421 @@ mod test {
             assert!(ray.start.z.is_finite());
         }
     }
+
+    #[test]
+    fn test_bvh_collide_cylinder() {
+        let triangles = generate_tris();
+
+        let bvh = Bvh::from(&triangles);
+
+        // a flat cylinder just above the floor doesn't touch it
+        let mut cylinder = Cylinder {
+            center: Vec3::new(100., 200., 32.),
+            radius: 114.25,
+            half_height: 31.25,
+            ..Default::default()
+        };
+        assert!(bvh.collide_cylinder(&cylinder).is_none());
+
+        // sinking into the floor, the contact is right under the cylinder
+        cylinder.center.z = 30.;
+        let contact = bvh.collide_cylinder(&cylinder).unwrap();
+        assert!((contact.direction.z - 1.).abs() < 0.0001);
+        assert!((contact.start.x - 100.).abs() < 0.01);
+        assert!((contact.start.y - 200.).abs() < 0.01);
+        assert!(contact.start.z.abs() < 0.01);
+
+        // the edge of a cylinder on its side touches the wall
+        cylinder.center = Vec3::new(-4096. + 100., 0., 500.);
+        let contact = bvh.collide_cylinder(&cylinder).unwrap();
+        assert!((contact.direction.x - 1.).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_bvh_sweep_sphere() {
+        let triangles = generate_tris();
+
+        let bvh = Bvh::from(&triangles);
+
+        // falling onto the floor
+        let sphere = Sphere {
+            center: Vec3::new(100., 200., 300.),
+            radius: 100.,
+        };
+        let hit = bvh.sweep_sphere(&sphere, Vec3::new(0., 0., -1000.)).unwrap();
+        assert!((hit.toi - 0.2).abs() < 0.0001);
+        assert!((hit.normal.z - 1.).abs() < 0.0001);
+        assert!((hit.point - Vec3::new(100., 200., 0.)).length() < 0.01);
+
+        // moving up or too slowly to reach the floor
+        assert!(bvh.sweep_sphere(&sphere, Vec3::new(0., 0., 1000.)).is_none());
+        assert!(bvh.sweep_sphere(&sphere, Vec3::new(0., 0., -150.)).is_none());
+
+        // a sweep that would go straight through the wall in one step
+        let sphere = Sphere {
+            center: Vec3::new(-3500., 0., 500.),
+            radius: 100.,
+        };
+        let hit = bvh.sweep_sphere(&sphere, Vec3::new(-2000., 0., 0.)).unwrap();
+        assert!((hit.toi - 0.248).abs() < 0.0001);
+        assert!((hit.normal.x - 1.).abs() < 0.0001);
+
+        // only the first hit is reported, and `keep` can skip the floor
+        let sphere = Sphere {
+            center: Vec3::new(-3700., 0., 300.),
+            radius: 100.,
+        };
+        let d = Vec3::new(-1000., 0., -1000.);
+        assert!((bvh.sweep_sphere(&sphere, d).unwrap().normal.z - 1.).abs() < 0.0001);
+        assert!((bvh.sweep_sphere_with(&sphere, d, |tri, _| tri.p.iter().any(|p| p.z != 0.)).unwrap().normal.x - 1.).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_bvh_raycast() {
+        let triangles = generate_tris();
+
+        let bvh = Bvh::from(&triangles);
+
+        // straight down onto the floor
+        let ray = Ray {
+            start: Vec3::new(100., 200., 500.),
+            direction: Vec3::new(0., 0., -1000.),
+        };
+        let hit = bvh.raycast(&ray).unwrap();
+        assert!((hit.distance - 500.).abs() < 0.01);
+        assert!((hit.point - Vec3::new(100., 200., 0.)).length() < 0.01);
+        assert!((hit.normal.z - 1.).abs() < 0.0001);
+        assert!(bvh.raycast_any(&ray));
+
+        // the ray ends before it reaches the floor
+        let ray = Ray {
+            direction: Vec3::new(0., 0., -400.),
+            ..ray
+        };
+        assert!(bvh.raycast(&ray).is_none());
+        assert!(!bvh.raycast_any(&ray));
+
+        // the floor is closer than the wall
+        let ray = Ray {
+            start: Vec3::new(-3000., 0., 500.),
+            direction: Vec3::new(-2000., 0., -1000.),
+        };
+        let hit = bvh.raycast(&ray).unwrap();
+        assert!((hit.point - Vec3::new(-4000., 0., 0.)).length() < 0.01);
+
+        // from outside of the arena, the back of the wall is hit and the normal faces the ray
+        let ray = Ray {
+            start: Vec3::new(-5000., 0., 500.),
+            direction: Vec3::new(2000., 0., 0.),
+        };
+        let hit = bvh.raycast(&ray).unwrap();
+        assert!((hit.distance - 904.).abs() < 0.01);
+        assert!((hit.normal.x + 1.).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_bvh_closest_point() {
+        let triangles = generate_tris();
+
+        let bvh = Bvh::from(&triangles);
+
+        let closest = bvh.closest_point(Vec3::new(100., 200., 50.), 1000.).unwrap();
+        assert!((closest.point - Vec3::new(100., 200., 0.)).length() < 0.01);
+        assert!((closest.normal.z - 1.).abs() < 0.0001);
+        assert!((closest.distance - 50.).abs() < 0.01);
+
+        // nothing is close enough
+        assert!(bvh.closest_point(Vec3::new(100., 200., 50.), 10.).is_none());
+        assert_eq!(bvh.signed_distance(Vec3::new(100., 200., 50.), 10.), 10.);
+
+        // the floor is closer than the wall
+        let closest = bvh.closest_point(Vec3::new(-4000., 0., 30.), 1000.).unwrap();
+        assert!((closest.normal.z - 1.).abs() < 0.0001);
+        assert!((bvh.signed_distance(Vec3::new(-4000., 0., 30.), 1000.) - 30.).abs() < 0.01);
+
+        // behind the wall
+        let closest = bvh.closest_point(Vec3::new(-4200., 0., 500.), 1000.).unwrap();
+        assert!((closest.normal.x - 1.).abs() < 0.0001);
+        assert!((bvh.signed_distance(Vec3::new(-4200., 0., 500.), 1000.) + 104.).abs() < 0.01);
+
+        // outside of the corner where the wall meets the floor, the closest point is on the edge
+        let p = Vec3::new(-4200., 0., -100.);
+        let closest = bvh.closest_point(p, 1000.).unwrap();
+        assert!((closest.point - Vec3::new(-4096., 0., 0.)).length() < 0.01);
+        assert!(bvh.signed_distance(p, 1000.) < 0.);
+    }
+
+    #[test]
+    fn test_bvh_collide_manifold() {
+        let triangles = generate_tris();
+
+        let bvh = Bvh::from(&triangles);
+
+        // in the corner between the floor and a wall, both are their own contact
+        let sphere = Sphere {
+            center: Vec3::new(-4096. + 90., 0., 80.),
+            radius: 100.,
+        };
+        let manifold = bvh.collide_manifold(&sphere);
+        assert_eq!(manifold.len(), 2);
+        assert!((manifold[0].normal.z - 1.).abs() < 0.0001);
+        assert!((manifold[0].depth - 20.).abs() < 0.001);
+        assert!((manifold[1].normal.x - 1.).abs() < 0.0001);
+        assert!((manifold[1].depth - 10.).abs() < 0.001);
+
+        // while `collide` averages them into one
+        let contact = bvh.collide(&sphere).unwrap();
+        assert!(contact.direction.x > 0.4 && contact.direction.z > 0.8);
+
+        // the floor is two triangles, but a sphere on their shared edge only touches it once
+        let sphere = Sphere {
+            center: Vec3::new(0., 0., 90.),
+            radius: 100.,
+        };
+        let manifold = bvh.collide_manifold(&sphere);
+        assert_eq!(manifold.len(), 1);
+        assert!((manifold[0].point - Vec3::new(0., 0., 0.)).length() < 0.001);
+
+        // a sphere that went through the floor is pushed back up, not down
+        let sphere = Sphere {
+            center: Vec3::new(100., 200., -10.),
+            radius: 100.,
+        };
+        let manifold = bvh.collide_manifold(&sphere);
+        assert!((manifold[0].normal.z - 1.).abs() < 0.0001);
+        assert!((manifold[0].depth - 110.).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_obb_intersect() {
+        // a box turned 45 degrees around z, its corner points along x
+        let obb = Obb {
+            center: Vec3::default(),
+            half_width: Vec3::new(100., 100., 50.),
+            orientation: Mat3 {
+                m: [[0.70710677, -0.70710677, 0.], [0.70710677, 0.70710677, 0.], [0., 0., 1.]],
+            },
+        };
+
+        // the bounding boxes overlap, but the box doesn't reach the corner of the aabb
+        let aabb = Aabb {
+            min: Vec3::new(80., 80., -10.),
+            max: Vec3::new(200., 200., 10.),
+        };
+        assert!(Aabb::from_obb(&obb).intersect_self(&aabb));
+        assert!(!obb.intersect_aabb(&aabb));
+
+        // but it does reach in front of its corner
+        let aabb = Aabb {
+            min: Vec3::new(130., -10., -10.),
+            max: Vec3::new(200., 10., 10.),
+        };
+        assert!(obb.intersect_aabb(&aabb));
+
+        let sphere = Sphere {
+            center: Vec3::new(100., 100., 0.),
+            radius: 10.,
+        };
+        assert!(!obb.intersect_sphere(&sphere));
+        assert!(obb.intersect_sphere(&Sphere {
+            radius: 50.,
+            ..sphere
+        }));
+
+        // a triangle just under the box, and one that it sits on
+        let mut tri = Tri {
+            p: [Vec3::new(-500., -500., -60.), Vec3::new(500., -500., -60.), Vec3::new(0., 500., -60.)],
+            ..Default::default()
+        };
+        assert!(!obb.intersect_tri(&tri));
+        for p in &mut tri.p {
+            p.z = -40.;
+        }
+        assert!(obb.intersect_tri(&tri));
+
+        // a triangle beside the corner of the box
+        let tri = Tri {
+            p: [Vec3::new(100., 100., -100.), Vec3::new(200., 100., -100.), Vec3::new(100., 100., 100.)],
+            ..Default::default()
+        };
+        assert!(!obb.intersect_tri(&tri));
+    }
+
+    #[test]
+    fn test_bvh_collide_obb() {
+        let triangles = generate_tris();
+
+        let bvh = Bvh::from(&triangles);
+
+        // a box sitting 5 units into the floor
+        let mut obb = Obb {
+            center: Vec3::new(100., 200., 15.),
+            half_width: Vec3::new(100., 50., 20.),
+            ..Default::default()
+        };
+        let manifold = bvh.collide_obb(&obb);
+        assert_eq!(manifold.len(), 4);
+        assert!(manifold.iter().all(|contact| (contact.depth - 5.).abs() < 0.001 && (contact.normal.z - 1.).abs() < 0.0001 && contact.point.z.abs() < 0.001));
+        assert!(!bvh.intersect_obb(&obb).is_empty());
+
+        // in the air
+        obb.center.z = 100.;
+        assert!(bvh.collide_obb(&obb).is_empty());
+        assert!(bvh.intersect_obb(&obb).is_empty());
+
+        // standing on one of its edges
+        obb.center.z = 30.;
+        obb.orientation = Mat3 {
+            m: [[1., 0., 0.], [0., 0.70710677, -0.70710677], [0., 0.70710677, 0.70710677]],
+        };
+        let manifold = bvh.collide_obb(&obb);
+        assert_eq!(manifold.len(), 2);
+        let depth = (50. + 20.) * 0.70710677 - 30.;
+        assert!(manifold.iter().all(|contact| (contact.depth - depth).abs() < 0.01 && (contact.point.y - (200. - 30. * 0.70710677)).abs() < 0.01));
+    }
+
+    #[test]
+    fn test_tri_sweep_sphere_edge() {
+        let tri = Tri {
+            p: [Vec3::new(0., 0., 0.), Vec3::new(100., 0., 0.), Vec3::new(0., 100., 0.)],
+            ..Default::default()
+        };
+
+        // passing beside the triangle, it can only clip the edge along the x axis
+        let sphere = Sphere {
+            center: Vec3::new(50., -60., 120.),
+            radius: 100.,
+        };
+        let hit = tri.sweep_sphere(&sphere, Vec3::new(0., 0., -200.)).unwrap();
+        assert!((hit.point - Vec3::new(50., 0., 0.)).length() < 0.01);
+        assert!((hit.toi - 0.2).abs() < 0.0001);
+        assert!((hit.normal - Vec3::new(0., -0.6, 0.8)).length() < 0.0001);
+
+        // and the corner at the origin
+        let sphere = Sphere {
+            center: Vec3::new(-60., -60., 100.),
+            radius: 100.,
+        };
+        let hit = tri.sweep_sphere(&sphere, Vec3::new(0., 0., -200.)).unwrap();
+        assert!(hit.point.length() < 0.01);
+
+        // the closest point of the triangle
+        assert!((tri.closest_point(Vec3::new(20., 20., 50.)) - Vec3::new(20., 20., 0.)).length() < 0.0001);
+        assert!((tri.closest_point(Vec3::new(100., 100., 0.)) - Vec3::new(50., 50., 0.)).length() < 0.0001);
+        assert!((tri.closest_point(Vec3::new(-10., -10., 0.)) - Vec3::default()).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_bvh_bin_roundtrip() {
+        let triangles = generate_tris();
+        let bvh = Bvh::build(&triangles, BvhBuilder::Sah);
+
+        let mut bin = Vec::new();
+        bvh.to_bin(&mut bin).unwrap();
+        let read = Bvh::from_bin(bin.as_slice()).unwrap();
+
+        assert_eq!(read.num_leaves, bvh.num_leaves);
+        assert_eq!(read.nodes.len(), bvh.nodes.len());
+        assert_eq!(read.primitives.len(), bvh.primitives.len());
+
+        for (a, b) in read.nodes.iter().zip(bvh.nodes.iter()) {
+            assert_eq!(a.box_.min.x, b.box_.min.x);
+            assert_eq!(a.box_.max.z, b.box_.max.z);
+
+            match (a.kind, b.kind) {
+                (BvhNodeKind::Leaf(a), BvhNodeKind::Leaf(b)) => assert_eq!(a, b),
+                (
+                    BvhNodeKind::Branch {
+                        right: a_right,
+                        left: a_left,
+                    },
+                    BvhNodeKind::Branch {
+                        right: b_right,
+                        left: b_left,
+                    },
+                ) => assert_eq!((a_right, a_left), (b_right, b_left)),
+                _ => panic!("node kinds don't match"),
+            }
+        }
+
+        for (a, b) in read.primitives.iter().zip(bvh.primitives.iter()) {
+            assert_eq!(a.p[2].y, b.p[2].y);
+            assert_eq!(a.surface, b.surface);
+        }
+
+        // writing it again gives the same bytes
+        let mut bin_again = Vec::new();
+        read.to_bin(&mut bin_again).unwrap();
+        assert_eq!(bin, bin_again);
+    }
+
+    #[test]
+    fn test_bvh_bin_custom_names() {
+        let mesh = Mesh {
+            ids: vec![0, 1, 2, 0, 2, 3],
+            vertices: vec![0., 0., 0., 100., 0., 0., 100., 100., 0., 0., 100., 0.],
+            ..Default::default()
+        };
+        let wall = Mesh {
+            ids: vec![0, 1, 2],
+            vertices: vec![0., 0., 0., 0., 100., 0., 0., 0., 100.],
+            ..Default::default()
+        };
+        let bvh = Bvh::from(&Mesh::from(vec![&mesh.tag("pitch", SurfaceKind::Floor), &wall.tag_by_normal("stand")]).to_triangles());
+
+        let mut bin = Vec::new();
+        bvh.to_bin(&mut bin).unwrap();
+        let read = Bvh::from_bin(bin.as_slice()).unwrap();
+
+        let names: Vec<(&str, SurfaceKind)> = read.primitives.iter().map(|tri| (&*tri.surface.name, tri.surface.kind)).collect();
+        assert_eq!(names, bvh.primitives.iter().map(|tri| (&*tri.surface.name, tri.surface.kind)).collect::<Vec<_>>());
+        assert_eq!(names.iter().filter(|&&(name, _)| name == "pitch").count(), 2);
+        assert!(names.contains(&("stand", SurfaceKind::Wall)));
+
+        // a name that from_bin wouldn't read back isn't written
+        let long = Bvh::from(&mesh.tag(&"x".repeat(Bvh::MAX_NAME_LEN + 1), SurfaceKind::Floor).to_triangles());
+        assert_eq!(long.to_bin(&mut Vec::new()).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_bvh_bin_errors() {
+        let triangles = generate_tris();
+        let mut bin = Vec::new();
+        Bvh::from(&triangles).to_bin(&mut bin).unwrap();
+
+        assert!(matches!(Bvh::from_bin(&b"RLMSH"[..]), Err(BvhError::NotABvh)));
+        assert!(matches!(Bvh::from_bin(&bin[..bin.len() - 3]), Err(BvhError::Io(_))));
+
+        let mut bad_version = bin.clone();
+        bad_version[5] = 9;
+        assert!(matches!(Bvh::from_bin(bad_version.as_slice()), Err(BvhError::UnsupportedVersion(9))));
+
+        // the last node is a leaf, point it past the end of the triangles
+        let mut bad_leaf = bin.clone();
+        let len = bad_leaf.len();
+        bad_leaf[len - 4..].copy_from_slice(&(triangles.len() as u32).to_le_bytes());
+        assert!(matches!(
+            Bvh::from_bin(bad_leaf.as_slice()),
+            Err(BvhError::IndexOutOfBounds {
+                what: "triangle",
+                ..
+            })
+        ));
+
+        // the first name's length is after the magic, the version and the number of names
+        let mut bad_name = bin.clone();
+        bad_name[10..14].copy_from_slice(&1u32.to_le_bytes());
+        bad_name.insert(14, 0xff);
+        assert!(matches!(Bvh::from_bin(bad_name.as_slice()), Err(BvhError::InvalidSurface(_))));
+
+        let mut long_name = bin.clone();
+        long_name[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(Bvh::from_bin(long_name.as_slice()), Err(BvhError::InvalidSurface(_))));
+
+        // the root pointing at itself isn't a tree
+        let mut cycle = bin.clone();
+        let root = len - 8 * (triangles.len() * 2 - 1);
+        cycle[root..root + 4].copy_from_slice(&0u32.to_le_bytes());
+        assert!(matches!(Bvh::from_bin(cycle.as_slice()), Err(BvhError::InvalidTree)));
+    }
 }
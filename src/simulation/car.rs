@@ -0,0 +1,141 @@
+use crate::linear_algebra::mat::Mat3;
+use crate::linear_algebra::math::dot;
+use crate::simulation::geometry::Obb;
+use crate::simulation::goal::BLUE_TEAM;
+use glam::Vec3;
+
+// Hitbox dimensions and offsets are taken from the RLBot wiki
+// The offset is relative to the car's location, in the car's local coordinates
+#[derive(Clone, Copy, Debug)]
+pub struct CarHitbox {
+    pub length: f32,
+    pub width: f32,
+    pub height: f32,
+    pub offset: Vec3,
+}
+
+impl Default for CarHitbox {
+    fn default() -> Self {
+        CarHitbox::octane()
+    }
+}
+
+impl CarHitbox {
+    pub fn octane() -> Self {
+        Self {
+            length: 118.0074,
+            width: 84.19941,
+            height: 36.15907,
+            offset: Vec3::new(13.87566, 0., 20.75499),
+        }
+    }
+
+    pub fn dominus() -> Self {
+        Self {
+            length: 127.9268,
+            width: 83.27995,
+            height: 31.3,
+            offset: Vec3::new(9., 0., 15.75),
+        }
+    }
+
+    pub fn plank() -> Self {
+        Self {
+            length: 128.8198,
+            width: 84.67036,
+            height: 29.3944,
+            offset: Vec3::new(9.008572, 0., 12.0942),
+        }
+    }
+
+    pub fn breakout() -> Self {
+        Self {
+            length: 131.4924,
+            width: 80.521,
+            height: 30.3,
+            offset: Vec3::new(12.5, 0., 11.75),
+        }
+    }
+
+    pub fn hybrid() -> Self {
+        Self {
+            length: 127.0192,
+            width: 82.18787,
+            height: 34.15907,
+            offset: Vec3::new(13.87566, 0., 20.75499),
+        }
+    }
+
+    pub fn merc() -> Self {
+        Self {
+            length: 120.7209,
+            width: 76.71031,
+            height: 41.6594,
+            offset: Vec3::new(11.37566, 0., 21.50499),
+        }
+    }
+
+    pub fn half_width(&self) -> Vec3 {
+        Vec3::new(self.length / 2., self.width / 2., self.height / 2.)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Car {
+    pub location: Vec3,
+    pub velocity: Vec3,
+    pub angular_velocity: Vec3,
+    // columns are the forward, left and up vectors of the car
+    pub orientation: Mat3,
+    pub hitbox: CarHitbox,
+    pub team: u8,
+}
+
+impl Default for Car {
+    fn default() -> Self {
+        Self {
+            location: Vec3::default(),
+            velocity: Vec3::default(),
+            angular_velocity: Vec3::default(),
+            orientation: Mat3::eye(),
+            hitbox: CarHitbox::default(),
+            team: BLUE_TEAM,
+        }
+    }
+}
+
+impl Car {
+    pub const M: f32 = 180.;
+
+    // diagonal of the car's inertia tensor, in local coordinates
+    const I: [f32; 3] = [751., 1334., 1836.];
+
+    pub fn forward(&self) -> Vec3 {
+        Vec3::new(self.orientation.m[0][0], self.orientation.m[1][0], self.orientation.m[2][0])
+    }
+
+    pub fn left(&self) -> Vec3 {
+        Vec3::new(self.orientation.m[0][1], self.orientation.m[1][1], self.orientation.m[2][1])
+    }
+
+    pub fn up(&self) -> Vec3 {
+        Vec3::new(self.orientation.m[0][2], self.orientation.m[1][2], self.orientation.m[2][2])
+    }
+
+    pub fn hitbox(&self) -> Obb {
+        Obb {
+            center: self.location + dot(self.orientation, self.hitbox.offset),
+            half_width: self.hitbox.half_width(),
+            orientation: self.orientation,
+        }
+    }
+
+    // the inverse inertia tensor, in world coordinates
+    pub fn inv_inertia(&self) -> Mat3 {
+        let local = Mat3 {
+            m: [[1. / Car::I[0], 0., 0.], [0., 1. / Car::I[1], 0.], [0., 0., 1. / Car::I[2]]],
+        };
+
+        self.orientation.dot(local).dot(self.orientation.transpose())
+    }
+}
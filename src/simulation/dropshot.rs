@@ -0,0 +1,317 @@
+use super::goal::{BLUE_TEAM, ORANGE_TEAM};
+use glam::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileState {
+    Intact,
+    Damaged,
+    Open,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+    pub location: Vec3,
+    // the team whose half of the field the tile is on
+    pub team: u8,
+    pub state: TileState,
+}
+
+// The hexagonal tiles that make up the dropshot floor
+// The tiles are "pointy-top" hexagons, with their points along the y axis
+#[derive(Clone, Debug)]
+pub struct DropshotTiles {
+    pub tiles: Vec<Tile>,
+    // the distance from the center of a tile to its points
+    pub tile_radius: f32,
+}
+
+impl Default for DropshotTiles {
+    fn default() -> Self {
+        // the tile locations aren't part of the dropshot mesh, so they're laid out so that
+        // 7 rows of tiles fit between the center line and the back wall on each side
+        // this layout hasn't been compared with the game's, bots can pass RLBot's tile locations to from_locations instead
+        let tile_radius = DropshotTiles::ARENA_APOTHEM / 11.;
+        let width = tile_radius * 3_f32.sqrt();
+
+        let mut locations = Vec::with_capacity(DropshotTiles::NUM_TILES);
+
+        for y_dir in [-1., 1.] {
+            for row in 0..DropshotTiles::NUM_ROWS {
+                let y = y_dir * tile_radius * (1. + 1.5 * row as f32);
+                let num_tiles = DropshotTiles::CENTER_ROW_TILES - row;
+
+                for i in 0..num_tiles {
+                    let x = (i as f32 - (num_tiles - 1) as f32 / 2.) * width;
+                    locations.push(Vec3::new(x, y, DropshotTiles::FLOOR_Z));
+                }
+            }
+        }
+
+        DropshotTiles::from_locations(&locations, tile_radius)
+    }
+}
+
+impl DropshotTiles {
+    // the distance from the center of the field to the walls in initialize_dropshot
+    const ARENA_APOTHEM: f32 = 11683.6 * 0.393;
+    const FLOOR_Z: f32 = 2.;
+
+    const NUM_ROWS: usize = 7;
+    const CENTER_ROW_TILES: usize = 13;
+    pub const NUM_TILES: usize = 140;
+
+    // Builds the tiles from a list of tile centers, like the ones in RLBot's field info
+    // The tiles are sorted by their y, then x coordinates, so the blue tiles come first
+    pub fn from_locations(locations: &[Vec3], tile_radius: f32) -> Self {
+        let mut tiles: Vec<Tile> = locations
+            .iter()
+            .map(|&location| Tile {
+                location,
+                team: if location.y < 0. {
+                    BLUE_TEAM
+                } else {
+                    ORANGE_TEAM
+                },
+                state: TileState::Intact,
+            })
+            .collect();
+
+        tiles.sort_by(|a, b| a.location.y.total_cmp(&b.location.y).then(a.location.x.total_cmp(&b.location.x)));
+
+        Self {
+            tiles,
+            tile_radius,
+        }
+    }
+
+    // distance between the centers of neighboring tiles
+    fn tile_width(&self) -> f32 {
+        self.tile_radius * 3_f32.sqrt()
+    }
+
+    // The index of the tile that `p` is above, if there is one
+    pub fn tile_at(&self, p: Vec3) -> Option<usize> {
+        let half_width = self.tile_width() / 2.;
+
+        self.tiles.iter().position(|tile| {
+            let dx = (p.x - tile.location.x).abs();
+            let dy = (p.y - tile.location.y).abs();
+
+            dx <= half_width && dy <= self.tile_radius - dx / 3_f32.sqrt()
+        })
+    }
+
+    // The indices of the tiles that are within `rings` tiles of the given tile, on the same side of the field
+    pub fn tiles_around(&self, index: usize, rings: usize) -> Vec<usize> {
+        let center = self.tiles[index].location;
+        let max_distance = (rings as f32 + 0.5) * self.tile_width();
+
+        self.tiles.iter().enumerate().filter(|(_, tile)| tile.team == self.tiles[index].team && (tile.location - center).length() < max_distance).map(|(i, _)| i).collect()
+    }
+
+    // Sets the state of every tile, in the same order as `tiles`
+    pub fn set_states(&mut self, states: &[TileState]) {
+        for (tile, &state) in self.tiles.iter_mut().zip(states) {
+            tile.state = state;
+        }
+    }
+
+    // Whether `p` is above a tile that has been broken open, the ball falls through these
+    pub fn is_open_at(&self, p: Vec3) -> bool {
+        self.tile_at(p).is_some_and(|i| self.tiles[i].state == TileState::Open)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChargeLevel {
+    Normal,
+    Charged,
+    SuperCharged,
+}
+
+impl ChargeLevel {
+    // how many rings of tiles around the one that's hit are damaged
+    pub fn rings(self) -> usize {
+        match self {
+            ChargeLevel::Normal => 0,
+            ChargeLevel::Charged => 1,
+            ChargeLevel::SuperCharged => 2,
+        }
+    }
+}
+
+// The state of a dropshot ball, it's part of every slice because it changes during the prediction
+#[derive(Clone, Copy, Debug)]
+pub struct DropshotBall {
+    pub charge: ChargeLevel,
+    // the sum of the speeds of every car touch since the ball last damaged the floor
+    pub accumulated_hit_force: f32,
+    // the team that touched the ball last, the ball only damages the other team's tiles
+    pub team: Option<u8>,
+    // the ball can only damage the floor once per touch
+    pub has_damaged: bool,
+}
+
+impl Default for DropshotBall {
+    fn default() -> Self {
+        Self {
+            charge: ChargeLevel::Normal,
+            accumulated_hit_force: 0.,
+            team: None,
+            has_damaged: false,
+        }
+    }
+}
+
+// The tiles that a floor hit damaged, only tiles that change state are listed
+#[derive(Clone, Debug)]
+pub struct TileDamage {
+    pub slice: usize,
+    pub time: f32,
+    pub location: Vec3,
+    // tiles that went from intact to damaged
+    pub damaged: Vec<usize>,
+    // tiles that went from damaged to open
+    pub broken: Vec<usize>,
+}
+
+impl DropshotBall {
+    // approximate, the game's exact thresholds aren't known
+    const CHARGED_THRESHOLD: f32 = 2500.;
+    const SUPER_CHARGED_THRESHOLD: f32 = 11000.;
+
+    pub fn on_touch(&mut self, team: u8, hit_force: f32) {
+        self.accumulated_hit_force += hit_force;
+        self.team = Some(team);
+        self.has_damaged = false;
+
+        if self.accumulated_hit_force >= DropshotBall::SUPER_CHARGED_THRESHOLD {
+            self.charge = ChargeLevel::SuperCharged;
+        } else if self.accumulated_hit_force >= DropshotBall::CHARGED_THRESHOLD {
+            self.charge = self.charge.max(ChargeLevel::Charged);
+        }
+    }
+
+    // Damages the tiles around `location`, if the ball can damage the tile that it landed on
+    // The charge is used up once the ball damages the floor
+    pub fn on_floor_contact(&mut self, tiles: &mut DropshotTiles, location: Vec3, time: f32, slice: usize) -> Option<TileDamage> {
+        let team = self.team?;
+
+        if self.has_damaged {
+            return None;
+        }
+
+        let hit = tiles.tile_at(location).filter(|&i| tiles.tiles[i].team != team)?;
+
+        let mut damage = TileDamage {
+            slice,
+            time,
+            location,
+            damaged: Vec::new(),
+            broken: Vec::new(),
+        };
+
+        for i in tiles.tiles_around(hit, self.charge.rings()) {
+            let tile = &mut tiles.tiles[i];
+
+            match tile.state {
+                TileState::Intact => {
+                    tile.state = TileState::Damaged;
+                    damage.damaged.push(i);
+                }
+                TileState::Damaged => {
+                    tile.state = TileState::Open;
+                    damage.broken.push(i);
+                }
+                TileState::Open => {}
+            }
+        }
+
+        self.charge = ChargeLevel::Normal;
+        self.accumulated_hit_force = 0.;
+        self.has_damaged = true;
+
+        Some(damage)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_default_layout() {
+        let tiles = DropshotTiles::default();
+
+        assert_eq!(tiles.tiles.len(), DropshotTiles::NUM_TILES);
+        assert_eq!(tiles.tiles.iter().filter(|tile| tile.team == BLUE_TEAM).count(), DropshotTiles::NUM_TILES / 2);
+        assert!(tiles.tiles[..DropshotTiles::NUM_TILES / 2].iter().all(|tile| tile.team == BLUE_TEAM));
+
+        // every tile is inside of its own hexagon and nobody else's
+        for (i, tile) in tiles.tiles.iter().enumerate() {
+            assert_eq!(tiles.tile_at(tile.location), Some(i));
+        }
+
+        // the middle of a tile has 6 neighbors, and 18 tiles within 2 rings
+        let middle = tiles.tile_at(Vec3::new(0., 2000., 0.)).unwrap();
+        assert_eq!(tiles.tiles_around(middle, 0), vec![middle]);
+        assert_eq!(tiles.tiles_around(middle, 1).len(), 7);
+        assert_eq!(tiles.tiles_around(middle, 2).len(), 19);
+    }
+
+    #[test]
+    fn check_tile_indices() {
+        let tiles = DropshotTiles::default();
+
+        // blue's back row, the center row on each side, and orange's back row
+        let expected = [(0, Vec3::new(-2168.9944, -4174.2314, 2.)), (6, Vec3::new(2168.9944, -4174.2314, 2.)), (7, Vec3::new(-2530.4934, -3548.097, 2.)), (63, Vec3::new(0., -417.42316, 2.)), (69, Vec3::new(4337.989, -417.42316, 2.)), (70, Vec3::new(-4337.989, 417.42316, 2.)), (76, Vec3::new(0., 417.42316, 2.)), (132, Vec3::new(2530.4934, 3548.097, 2.)), (139, Vec3::new(2168.9944, 4174.2314, 2.))];
+
+        for (i, location) in expected {
+            assert!((tiles.tiles[i].location - location).length() < 0.01, "tile {} is at {:?}", i, tiles.tiles[i].location);
+        }
+
+        // building the tiles from the same locations in any order gives the same indices
+        let mut locations: Vec<Vec3> = tiles.tiles.iter().map(|tile| tile.location).rev().collect();
+        locations.swap(3, 100);
+        let rebuilt = DropshotTiles::from_locations(&locations, tiles.tile_radius);
+
+        for (a, b) in rebuilt.tiles.iter().zip(&tiles.tiles) {
+            assert_eq!(a.location, b.location);
+            assert_eq!(a.team, b.team);
+        }
+    }
+
+    #[test]
+    fn check_floor_damage() {
+        let mut tiles = DropshotTiles::default();
+        let mut ball = DropshotBall::default();
+        let p = Vec3::new(0., 2000., 0.);
+        let hit = tiles.tile_at(p).unwrap();
+
+        // nobody has touched the ball yet
+        assert!(ball.on_floor_contact(&mut tiles, p, 0., 0).is_none());
+
+        // the orange team can't damage its own tiles
+        ball.on_touch(ORANGE_TEAM, 1000.);
+        assert!(ball.on_floor_contact(&mut tiles, p, 0., 0).is_none());
+
+        ball.on_touch(BLUE_TEAM, 1000.);
+        let damage = ball.on_floor_contact(&mut tiles, p, 0., 0).unwrap();
+        assert_eq!(damage.damaged, vec![hit]);
+        assert!(damage.broken.is_empty());
+
+        // only one hit per touch
+        assert!(ball.on_floor_contact(&mut tiles, p, 0., 0).is_none());
+
+        ball.on_touch(BLUE_TEAM, 3000.);
+        assert_eq!(ball.charge, ChargeLevel::Charged);
+        let damage = ball.on_floor_contact(&mut tiles, p, 0., 0).unwrap();
+        assert_eq!(damage.broken, vec![hit]);
+        assert_eq!(damage.damaged.len(), 6);
+        assert_eq!(tiles.tiles[hit].state, TileState::Open);
+        assert!(tiles.is_open_at(p));
+
+        // the charge is used up
+        assert_eq!(ball.charge, ChargeLevel::Normal);
+    }
+}
@@ -1,10 +1,12 @@
 use std::f32::consts::{FRAC_PI_3, FRAC_PI_6};
 
 use super::bvh::Bvh;
+use super::geometry::{SurfaceKind, Tri};
 use super::mesh::Mesh;
+use crate::error::MeshError;
 use crate::linear_algebra::mat::Mat3;
 use crate::linear_algebra::math::{axis_to_rotation, dot};
-use vvec3::Vec3;
+use glam::Vec3;
 
 static FLIP_X: Mat3 = Mat3 {
     m: [[-1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
@@ -14,32 +16,66 @@ static FLIP_Y: Mat3 = Mat3 {
     m: [[1., 0., 0.], [0., -1., 0.], [0., 0., 1.]],
 };
 
+// Triangles past the goal line are part of the goal, the rest are classified by the direction they face
+fn goal_kind(goal_y: f32) -> impl Fn(&Tri) -> SurfaceKind {
+    move |tri| {
+        if tri.center().y.abs() > goal_y {
+            SurfaceKind::Goal
+        } else {
+            SurfaceKind::from_normal(&tri.unit_normal())
+        }
+    }
+}
+
 fn quad(p: Vec3, e1: Vec3, e2: Vec3) -> Mesh {
     let vertices: [Vec3; 4] = [p + e1 + e2, p - e1 + e2, p - e1 - e2, p + e1 - e2];
 
     Mesh {
         ids: vec![0, 1, 3, 1, 2, 3],
         vertices: vec![vertices[0].x as f32, vertices[0].y as f32, vertices[0].z as f32, vertices[1].x as f32, vertices[1].y as f32, vertices[1].z as f32, vertices[2].x as f32, vertices[2].y as f32, vertices[2].z as f32, vertices[3].x as f32, vertices[3].y as f32, vertices[3].z as f32],
+        surfaces: Vec::new(),
+    }
+}
+
+pub fn initialize_custom(meshes: &[Mesh]) -> Result<Bvh, MeshError> {
+    for mesh in meshes {
+        mesh.validate()?;
+    }
+
+    if meshes.is_empty() {
+        return Err(MeshError::Empty);
     }
+
+    let field_mesh = Mesh::from(meshes.iter().collect());
+
+    let triangles = field_mesh.to_triangles();
+
+    Ok(Bvh::from(&triangles))
 }
 
 pub fn initialize_soccar(soccar_corner: &Mesh, soccar_goal: &Mesh, soccar_ramps_0: &Mesh, soccar_ramps_1: &Mesh) -> Bvh {
-    let floor = quad(Vec3::default(), Vec3::new(4096., 0., 0.), Vec3::new(0., 5120., 0.));
+    let floor = quad(Vec3::default(), Vec3::new(4096., 0., 0.), Vec3::new(0., 5120., 0.)).tag("floor", SurfaceKind::Floor);
 
-    let ceiling = quad(Vec3::new(0., 0., 2048.), Vec3::new(-4096., 0., 0.), Vec3::new(0., 5120., 0.));
+    let ceiling = quad(Vec3::new(0., 0., 2048.), Vec3::new(-4096., 0., 0.), Vec3::new(0., 5120., 0.)).tag("ceiling", SurfaceKind::Ceiling);
 
-    let side_walls = [quad(Vec3::new(4096., 0., 1024.), Vec3::new(0., -5120., 0.), Vec3::new(0., 0., 1024.)), quad(Vec3::new(-4096., 0., 1024.), Vec3::new(0., 5120., 0.), Vec3::new(0., 0., 1024.))];
+    let side_walls = [quad(Vec3::new(4096., 0., 1024.), Vec3::new(0., -5120., 0.), Vec3::new(0., 0., 1024.)).tag("side_wall", SurfaceKind::Wall), quad(Vec3::new(-4096., 0., 1024.), Vec3::new(0., 5120., 0.), Vec3::new(0., 0., 1024.)).tag("side_wall", SurfaceKind::Wall)];
+
+    let soccar_corner = soccar_corner.tag_by_normal("soccar_corner");
+    let blue_goal = soccar_goal.translate(Vec3::new(0., -5120., 0.));
+    let orange_goal = blue_goal.transform(FLIP_Y);
+    let soccar_ramps_0 = soccar_ramps_0.tag("soccar_ramps_0", SurfaceKind::Ramp);
+    let soccar_ramps_1 = soccar_ramps_1.tag("soccar_ramps_1", SurfaceKind::Ramp);
 
     let field_mesh = Mesh::from(vec![
-        soccar_corner,
+        &soccar_corner,
         &soccar_corner.transform(FLIP_X),
         &soccar_corner.transform(FLIP_Y),
         &soccar_corner.transform(FLIP_X.dot(FLIP_Y)),
-        &soccar_goal.translate(Vec3::new(0., -5120., 0.)),
-        &soccar_goal.translate(Vec3::new(0., -5120., 0.)).transform(FLIP_Y),
-        soccar_ramps_0,
+        &blue_goal.tag_with("blue_goal", goal_kind(5120.)),
+        &orange_goal.tag_with("orange_goal", goal_kind(5120.)),
+        &soccar_ramps_0,
         &soccar_ramps_0.transform(FLIP_X),
-        soccar_ramps_1,
+        &soccar_ramps_1,
         &soccar_ramps_1.transform(FLIP_X),
         &floor,
         &ceiling,
@@ -64,26 +100,30 @@ pub fn initialize_hoops(hoops_corner: &Mesh, hoops_net: &Mesh, hoops_rim: &Mesh,
     let transformed_hoops_net = hoops_net.transform(s).translate(dy);
     let transformed_hoops_rim = hoops_rim.transform(s).translate(dy);
 
-    let floor = quad(Vec3::default(), Vec3::new(2966., 0., 0.), Vec3::new(0., 3581., 0.));
+    let floor = quad(Vec3::default(), Vec3::new(2966., 0., 0.), Vec3::new(0., 3581., 0.)).tag("floor", SurfaceKind::Floor);
+
+    let ceiling = quad(Vec3::new(0., 0., 1820.), Vec3::new(-2966., 0., 0.), Vec3::new(0., 3581., 0.)).tag("ceiling", SurfaceKind::Ceiling);
 
-    let ceiling = quad(Vec3::new(0., 0., 1820.), Vec3::new(-2966., 0., 0.), Vec3::new(0., 3581., 0.));
+    let side_walls = [quad(Vec3::new(2966., 0., 910.), Vec3::new(0., -3581., 0.), Vec3::new(0., 0., 910.)).tag("side_wall", SurfaceKind::Wall), quad(Vec3::new(-2966., 0., 910.), Vec3::new(0., 3581., 0.), Vec3::new(0., 0., 910.)).tag("side_wall", SurfaceKind::Wall)];
 
-    let side_walls = [quad(Vec3::new(2966., 0., 910.), Vec3::new(0., -3581., 0.), Vec3::new(0., 0., 910.)), quad(Vec3::new(-2966., 0., 910.), Vec3::new(0., 3581., 0.), Vec3::new(0., 0., 910.))];
+    let back_walls = [quad(Vec3::new(0., 3581., 910.), Vec3::new(2966., 0., 0.), Vec3::new(0., 0., 910.)).tag("back_wall", SurfaceKind::Wall), quad(Vec3::new(0., -3581., 910.), Vec3::new(-2966., 0., 0.), Vec3::new(0., 0., 910.)).tag("back_wall", SurfaceKind::Wall)];
 
-    let back_walls = [quad(Vec3::new(0., 0., 1024.), Vec3::new(0., -5120., 0.), Vec3::new(0., 0., 1024.)), quad(Vec3::new(0., 0., 1024.), Vec3::new(0., 5120., 0.), Vec3::new(0., 0., 1024.))];
+    let hoops_corner = hoops_corner.tag_by_normal("hoops_corner");
+    let hoops_ramps_0 = hoops_ramps_0.tag("hoops_ramps_0", SurfaceKind::Ramp);
+    let hoops_ramps_1 = hoops_ramps_1.tag("hoops_ramps_1", SurfaceKind::Ramp);
 
     let field_mesh = Mesh::from(vec![
-        hoops_corner,
+        &hoops_corner,
         &hoops_corner.transform(FLIP_X),
         &hoops_corner.transform(FLIP_Y),
         &hoops_corner.transform(FLIP_X.dot(FLIP_Y)),
-        &transformed_hoops_net,
-        &transformed_hoops_net.transform(FLIP_Y),
-        &transformed_hoops_rim,
-        &transformed_hoops_rim.transform(FLIP_Y),
-        hoops_ramps_0,
+        &transformed_hoops_net.tag("orange_net", SurfaceKind::Goal),
+        &transformed_hoops_net.transform(FLIP_Y).tag("blue_net", SurfaceKind::Goal),
+        &transformed_hoops_rim.tag("orange_rim", SurfaceKind::Rim),
+        &transformed_hoops_rim.transform(FLIP_Y).tag("blue_rim", SurfaceKind::Rim),
+        &hoops_ramps_0,
         &hoops_ramps_0.transform(FLIP_X),
-        hoops_ramps_1,
+        &hoops_ramps_1,
         &hoops_ramps_1.transform(FLIP_Y),
         &floor,
         &ceiling,
@@ -111,8 +151,8 @@ pub fn initialize_dropshot(dropshot: &Mesh) -> Bvh {
 
     let dz = Vec3::new(0., 0., z_offset);
 
-    let floor = quad(Vec3::new(0., 0., 2.), Vec3::new(10000., 0., 0.), Vec3::new(0., 7000., 0.));
-    let ceiling = quad(Vec3::new(0., 0., 2020.), Vec3::new(-10000., 0., 0.), Vec3::new(0., 7000., 0.));
+    let floor = quad(Vec3::new(0., 0., 2.), Vec3::new(10000., 0., 0.), Vec3::new(0., 7000., 0.)).tag("floor", SurfaceKind::Floor);
+    let ceiling = quad(Vec3::new(0., 0., 2020.), Vec3::new(-10000., 0., 0.), Vec3::new(0., 7000., 0.)).tag("ceiling", SurfaceKind::Ceiling);
     let mut walls: Vec<Mesh> = Vec::with_capacity(6);
 
     let mut p = Vec3::new(0., 11683.6 * scale, 2768.64 * scale - z_offset);
@@ -121,12 +161,19 @@ pub fn initialize_dropshot(dropshot: &Mesh) -> Bvh {
     let r = axis_to_rotation(Vec3::new(0., 0., FRAC_PI_3));
 
     for _ in 0..6 {
-        walls.push(quad(p, x, z));
+        walls.push(quad(p, x, z).tag("wall", SurfaceKind::Wall));
         p = dot(r, p);
         x = dot(r, x);
     }
 
-    let field_mesh = Mesh::from(vec![&dropshot.transform(q.dot(s)).translate(dz), &floor, &ceiling, &walls[0], &walls[1], &walls[2], &walls[3], &walls[4], &walls[5]]);
+    // the upward-facing parts of the mesh are where the floor curves up into the walls, they aren't tagged as floor
+    // so that only the floor quad is dropped under tiles that have been broken open
+    let dropshot = dropshot.transform(q.dot(s)).translate(dz).tag_with("dropshot", |tri| match SurfaceKind::from_normal(&tri.unit_normal()) {
+        SurfaceKind::Floor => SurfaceKind::Ramp,
+        kind => kind,
+    });
+
+    let field_mesh = Mesh::from(vec![&dropshot, &floor, &ceiling, &walls[0], &walls[1], &walls[2], &walls[3], &walls[4], &walls[5]]);
 
     let triangles = field_mesh.to_triangles();
 
@@ -166,17 +213,23 @@ pub fn initialize_throwback(
         m: [[scale, 0., 0.], [0., scale, 0.], [0., 0., scale]],
     };
 
-    let floor = quad(Vec3::default(), Vec3::new(4096.6, 0., 0.), Vec3::new(0., 6910., 0.));
-    let ceiling = quad(Vec3::new(0., 0., 2048.), Vec3::new(-4096.6, 0., 0.), Vec3::new(0., 6910., 0.));
-    let side_walls: [Mesh; 2] = [quad(Vec3::new(4096.6, 0., 1024.), Vec3::new(0., -6910., 0.), Vec3::new(0., 0., 1024.)), quad(Vec3::new(-4096.6, 0., 1024.), Vec3::new(0., 6910., 0.), Vec3::new(0., 0., 1024.))];
+    let floor = quad(Vec3::default(), Vec3::new(4096.6, 0., 0.), Vec3::new(0., 6910., 0.)).tag("floor", SurfaceKind::Floor);
+    let ceiling = quad(Vec3::new(0., 0., 2048.), Vec3::new(-4096.6, 0., 0.), Vec3::new(0., 6910., 0.)).tag("ceiling", SurfaceKind::Ceiling);
+    let side_walls: [Mesh; 2] = [quad(Vec3::new(4096.6, 0., 1024.), Vec3::new(0., -6910., 0.), Vec3::new(0., 0., 1024.)).tag("side_wall", SurfaceKind::Wall), quad(Vec3::new(-4096.6, 0., 1024.), Vec3::new(0., 6910., 0.), Vec3::new(0., 0., 1024.)).tag("side_wall", SurfaceKind::Wall)];
+
+    let back_walls: [Mesh; 2] = [quad(Vec3::new(0., 6910., 1024.), Vec3::new(4096., 0., 0.), Vec3::new(0., 0., 1024.)).tag("back_wall", SurfaceKind::Wall), quad(Vec3::new(0., -6910., 1024.), Vec3::new(-4096., 0., 0.), Vec3::new(0., 0., 1024.)).tag("back_wall", SurfaceKind::Wall)];
 
-    let back_walls: [Mesh; 2] = [quad(Vec3::new(0., 6910., 1024.), Vec3::new(4096., 0., 0.), Vec3::new(0., 0., 1024.)), quad(Vec3::new(0., -6910., 1024.), Vec3::new(-4096., 0., 0.), Vec3::new(0., 0., 1024.))];
+    let corner_ramps_lower = corner_ramps_lower.tag("throwback_corner_ramps_lower", SurfaceKind::Ramp);
+    let corner_ramps_upper = corner_ramps_upper.tag("throwback_corner_ramps_upper", SurfaceKind::Ramp);
+    let corner_wall_0 = corner_wall_0.tag_by_normal("throwback_corner_wall_0");
+    let corner_wall_1 = corner_wall_1.tag_by_normal("throwback_corner_wall_1");
+    let corner_wall_2 = corner_wall_2.tag_by_normal("throwback_corner_wall_2");
 
     let throwback_goal = goal.transform(s);
-    let throwback_side_ramps_lower = side_ramps_lower.transform(s);
-    let throwback_side_ramps_upper = side_ramps_upper.transform(s);
-    let throwback_back_ramps_lower = back_ramps_lower.transform(s);
-    let throwback_back_ramps_upper = back_ramps_upper.transform(s);
+    let throwback_side_ramps_lower = side_ramps_lower.transform(s).tag("throwback_side_ramps_lower", SurfaceKind::Ramp);
+    let throwback_side_ramps_upper = side_ramps_upper.transform(s).tag("throwback_side_ramps_upper", SurfaceKind::Ramp);
+    let throwback_back_ramps_lower = back_ramps_lower.transform(s).tag("throwback_back_ramps_lower", SurfaceKind::Ramp);
+    let throwback_back_ramps_upper = back_ramps_upper.transform(s).tag("throwback_back_ramps_upper", SurfaceKind::Ramp);
     let throwback_corner_ramps_lower = corner_ramps_lower.transform(s);
     let throwback_corner_ramps_lower_y_flip = corner_ramps_lower.transform(FLIP_Y);
     let throwback_corner_ramps_upper = corner_ramps_upper.transform(s);
@@ -197,8 +250,8 @@ pub fn initialize_throwback(
         &throwback_corner_ramps_upper.transform(FLIP_X),
         &throwback_corner_ramps_upper_y_flip,
         &throwback_corner_ramps_upper_y_flip.transform(FLIP_X),
-        &throwback_goal,
-        &throwback_goal.transform(FLIP_Y),
+        &throwback_goal.tag_with("orange_goal", goal_kind(4885.)),
+        &throwback_goal.transform(FLIP_Y).tag_with("blue_goal", goal_kind(4885.)),
         &throwback_side_ramps_lower,
         &throwback_side_ramps_lower.transform(FLIP_X),
         &throwback_side_ramps_upper,
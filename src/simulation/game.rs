@@ -1,20 +1,58 @@
-use super::ball::Ball;
+use super::ball::{Ball, BallPhysicsParams, BallSolver};
 use super::bvh::Bvh;
-use vvec3::Vec3;
+use super::dropshot::DropshotTiles;
+use super::goal::Goal;
+use super::puck::Puck;
+use glam::Vec3;
+use std::sync::Arc;
 
+// The parts of a game that don't change while it's simulated
+// It's shared between every clone of a game, so many threads can predict against one collision mesh
+// Use `Arc::make_mut` to change it, which only copies it if it's shared
+#[derive(Clone)]
+pub struct Arena {
+    pub collision_mesh: Bvh,
+    pub goals: Vec<Goal>,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self {
+            collision_mesh: Bvh::default(),
+            goals: Vec::new(),
+        }
+    }
+}
+
+// The state of one simulation in an arena, cloning it doesn't copy the arena
 #[derive(Clone)]
 pub struct Game {
     pub gravity: Vec3,
-    pub collision_mesh: Bvh,
+    pub arena: Arc<Arena>,
     pub ball: Ball,
+    pub ball_physics: BallPhysicsParams,
+    pub record_contacts: bool,
+    // sweep the ball along its path when it isn't touching anything, so that a fast ball or a big dt can't tunnel through thin geometry
+    pub continuous_collision: bool,
+    pub ball_solver: BallSolver,
+    // only in snow day games
+    pub puck: Option<Puck>,
+    // only in dropshot games, the prediction damages a copy of these and leaves them as they were
+    pub dropshot_tiles: Option<DropshotTiles>,
 }
 
 impl Default for Game {
     fn default() -> Self {
         Self {
             gravity: Vec3::default(),
-            collision_mesh: Bvh::default(),
+            arena: Arc::default(),
             ball: Ball::default(),
+            ball_physics: BallPhysicsParams::default(),
+            record_contacts: false,
+            continuous_collision: false,
+            ball_solver: BallSolver::default(),
+            puck: None,
+            dropshot_tiles: None,
         }
     }
 }
@@ -1,15 +1,17 @@
 use crate::linear_algebra::mat::Mat3;
 use crate::linear_algebra::math::dot;
-use vvec3::Vec3;
+use glam::Vec3;
+use std::sync::Arc;
 
 pub fn distance_between(start: &Vec3, dir: &Vec3, p: &Vec3) -> f32 {
-    let u = ((*p - *start).dot(dir) / dir.dot(dir)).clamp(0., 1.);
-    (*start + *dir * u - *p).magnitude()
+    let u = ((*p - *start).dot(*dir) / dir.dot(*dir)).clamp(0., 1.);
+    (*start + *dir * u - *p).length()
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Tri {
     pub p: [Vec3; 3],
+    pub surface: Surface,
 }
 
 impl Tri {
@@ -18,7 +20,154 @@ impl Tri {
     }
 
     pub fn unit_normal(&self) -> Vec3 {
-        (self.p[1] - self.p[0]).cross(&(self.p[2] - self.p[0])).normalize()
+        (self.p[1] - self.p[0]).cross(self.p[2] - self.p[0]).normalize_or_zero()
+    }
+
+    // Whether a point on the triangle's plane is inside of the triangle
+    pub fn contains(&self, p: Vec3) -> bool {
+        let n = (self.p[1] - self.p[0]).cross(self.p[2] - self.p[0]);
+
+        (0..3).all(|i| (self.p[(i + 1) % 3] - self.p[i]).cross(p - self.p[i]).dot(n) >= 0.)
+    }
+
+    // The point on the triangle that is the closest to `p`
+    // From Real-Time Collision Detection by Christer Ericson, section 5.1.5
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let [a, b, c] = self.p;
+        let ab = b - a;
+        let ac = c - a;
+
+        let ap = p - a;
+        let d1 = ab.dot(ap);
+        let d2 = ac.dot(ap);
+        if d1 <= 0. && d2 <= 0. {
+            return a;
+        }
+
+        let bp = p - b;
+        let d3 = ab.dot(bp);
+        let d4 = ac.dot(bp);
+        if d3 >= 0. && d4 <= d3 {
+            return b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0. && d1 >= 0. && d3 <= 0. {
+            return a + ab * (d1 / (d1 - d3));
+        }
+
+        let cp = p - c;
+        let d5 = ab.dot(cp);
+        let d6 = ac.dot(cp);
+        if d6 >= 0. && d5 <= d6 {
+            return c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0. && d2 >= 0. && d6 <= 0. {
+            return a + ac * (d2 / (d2 - d6));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0. && d4 - d3 >= 0. && d5 - d6 >= 0. {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+
+        let denom = 1. / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+
+    // Moves the sphere along `d` and finds the first time that it touches the triangle
+    // Only the front of the triangle can be hit, so a sphere that's moving away from its normal never hits it
+    pub fn sweep_sphere(&self, s: &Sphere, d: Vec3) -> Option<SweepHit> {
+        let n = self.unit_normal();
+
+        if d.dot(n) >= 0. {
+            return None;
+        }
+
+        // already touching
+        let closest = self.closest_point(s.center);
+        if (s.center - closest).length() <= s.radius {
+            return Some(SweepHit {
+                toi: 0.,
+                point: closest,
+                normal: n,
+                surface: self.surface.clone(),
+            });
+        }
+
+        // the face, the sphere can only hit it if it starts in front of it
+        let distance = (s.center - self.p[0]).dot(n);
+        if distance > s.radius {
+            let toi = (distance - s.radius) / -d.dot(n);
+            let point = s.center + d * toi - n * s.radius;
+
+            if toi > 1. {
+                return None;
+            }
+
+            if self.contains(point) {
+                return Some(SweepHit {
+                    toi,
+                    point,
+                    normal: n,
+                    surface: self.surface.clone(),
+                });
+            }
+        }
+
+        // the sphere missed the face, so it can only hit the edges and the corners
+        let mut hit: Option<(f32, Vec3)> = None;
+
+        for i in 0..3 {
+            let a = self.p[i];
+            let e = self.p[(i + 1) % 3] - a;
+
+            for (toi, point) in sweep_sphere_corner(s, d, a).into_iter().chain(sweep_sphere_edge(s, d, a, e)) {
+                if hit.is_none_or(|(best, _)| toi < best) {
+                    hit = Some((toi, point));
+                }
+            }
+        }
+
+        hit.map(|(toi, point)| SweepHit {
+            toi,
+            point,
+            normal: (s.center + d * toi - point).normalize_or_zero(),
+            surface: self.surface.clone(),
+        })
+    }
+
+    // Where the ray hits the triangle, as a fraction of the ray's direction
+    // Both sides of the triangle can be hit
+    // From "Fast, Minimum Storage Ray/Triangle Intersection" by Tomas Möller and Ben Trumbore
+    pub fn raycast(&self, ray: &Ray) -> Option<f32> {
+        let e1 = self.p[1] - self.p[0];
+        let e2 = self.p[2] - self.p[0];
+
+        let p = ray.direction.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inv_det = 1. / det;
+        let s = ray.start - self.p[0];
+        let u = s.dot(p) * inv_det;
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(e1);
+        let v = ray.direction.dot(q) * inv_det;
+        if v < 0. || u + v > 1. {
+            return None;
+        }
+
+        let t = e2.dot(q) * inv_det;
+
+        (0. ..=1.).contains(&t).then_some(t)
     }
 
     #[allow(clippy::many_single_char_names)]
@@ -26,7 +175,7 @@ impl Tri {
         let e1 = self.p[1] - self.p[0];
         let e2 = self.p[2] - self.p[1];
         let e3 = self.p[0] - self.p[2];
-        let n = (&e3).cross(&e1).normalize();
+        let n = e3.cross(e1).normalize_or_zero();
 
         let a = Mat3 {
             m: [[e1.x, -e3.x, n.x], [e1.y, -e3.y, n.y], [e1.z, -e3.z, n.z]],
@@ -55,10 +204,53 @@ impl Tri {
     }
 }
 
+// When a sphere moving along `d` first touches the point `p`
+fn sweep_sphere_corner(s: &Sphere, d: Vec3, p: Vec3) -> Option<(f32, Vec3)> {
+    let m = s.center - p;
+
+    let a = d.dot(d);
+    let b = m.dot(d);
+    let c = m.dot(m) - s.radius * s.radius;
+
+    let discriminant = b * b - a * c;
+    if a == 0. || discriminant < 0. {
+        return None;
+    }
+
+    let toi = (-b - discriminant.sqrt()) / a;
+
+    (0. ..=1.).contains(&toi).then_some((toi, p))
+}
+
+// When a sphere moving along `d` first touches the edge from `p` to `p + e`, not counting the ends of the edge
+fn sweep_sphere_edge(s: &Sphere, d: Vec3, p: Vec3, e: Vec3) -> Option<(f32, Vec3)> {
+    let m = s.center - p;
+
+    let ee = e.dot(e);
+    let me = m.dot(e);
+    let de = d.dot(e);
+
+    // the distance from the line, ignoring the part of the motion that's along the edge
+    let a = ee * d.dot(d) - de * de;
+    let b = ee * m.dot(d) - me * de;
+    let c = ee * (m.dot(m) - s.radius * s.radius) - me * me;
+
+    let discriminant = b * b - a * c;
+    if a.abs() < f32::EPSILON * ee || discriminant < 0. {
+        return None;
+    }
+
+    let toi = (-b - discriminant.sqrt()) / a;
+    let along = (me + toi * de) / ee;
+
+    ((0. ..=1.).contains(&toi) && (0. ..=1.).contains(&along)).then_some((toi, p + e * along))
+}
+
 impl Default for Tri {
     fn default() -> Self {
         Self {
             p: [Vec3::default(), Vec3::default(), Vec3::default()],
+            surface: Surface::default(),
         }
     }
 }
@@ -83,11 +275,17 @@ impl Default for Aabb {
 impl Aabb {
     pub fn add(&self, b: &Aabb) -> Self {
         Self {
-            min: self.min.min(&b.min),
-            max: self.max.max(&b.max),
+            min: self.min.min(b.min),
+            max: self.max.max(b.max),
         }
     }
 
+    pub fn surface_area(&self) -> f32 {
+        let d = self.max - self.min;
+
+        2. * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
     pub fn from_tri(t: &Tri) -> Self {
         let min = Vec3::new(t.p[0].x.min(t.p[1].x.min(t.p[2].x)), t.p[0].y.min(t.p[1].y.min(t.p[2].y)), t.p[0].z.min(t.p[1].z.min(t.p[2].z)));
         let max = Vec3::new(t.p[0].x.max(t.p[1].x.max(t.p[2].x)), t.p[0].y.max(t.p[1].y.max(t.p[2].y)), t.p[0].z.max(t.p[1].z.max(t.p[2].z)));
@@ -98,6 +296,17 @@ impl Aabb {
         }
     }
 
+    pub fn from_obb(b: &Obb) -> Self {
+        let m = b.orientation.m;
+        let e = b.half_width;
+        let radius = Vec3::new(m[0][0].abs() * e.x + m[0][1].abs() * e.y + m[0][2].abs() * e.z, m[1][0].abs() * e.x + m[1][1].abs() * e.y + m[1][2].abs() * e.z, m[2][0].abs() * e.x + m[2][1].abs() * e.y + m[2][2].abs() * e.z);
+
+        Self {
+            min: b.center - radius,
+            max: b.center + radius,
+        }
+    }
+
     pub fn from_sphere(s: &Sphere) -> Self {
         let radius = Vec3::new(s.radius, s.radius, s.radius);
 
@@ -111,10 +320,93 @@ impl Aabb {
         (self.min.x <= b.max.x) & (self.max.x >= b.min.x) & (self.min.y <= b.max.y) & (self.max.y >= b.min.y) & (self.min.z <= b.max.z) & (self.max.z >= b.min.z)
     }
 
+    // Where the ray enters the box, as a fraction of the ray's direction
+    // A ray that starts inside of the box enters it at 0
+    pub fn intersect_ray(&self, ray: &Ray, max_t: f32) -> Option<f32> {
+        let mut t_min: f32 = 0.;
+        let mut t_max = max_t;
+
+        for (start, direction, min, max) in [(ray.start.x, ray.direction.x, self.min.x, self.max.x), (ray.start.y, ray.direction.y, self.min.y, self.max.y), (ray.start.z, ray.direction.z, self.min.z, self.max.z)] {
+            if direction == 0. {
+                if start < min || start > max {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let t1 = (min - start) / direction;
+            let t2 = (max - start) / direction;
+
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+        }
+
+        (t_min <= t_max).then_some(t_min)
+    }
+
+    // The squared distance from `p` to the closest point of the box, 0 if `p` is inside of the box
+    pub fn distance_squared(&self, p: Vec3) -> f32 {
+        let d = p - Vec3::new(p.x.clamp(self.min.x, self.max.x), p.y.clamp(self.min.y, self.max.y), p.z.clamp(self.min.z, self.max.z));
+
+        d.dot(d)
+    }
+
     pub fn intersect_sphere(&self, b: &Sphere) -> bool {
         let nearest = Vec3::new(b.center.x.clamp(self.min.x, self.max.x), b.center.y.clamp(self.min.y, self.max.y), b.center.z.clamp(self.min.z, self.max.z));
 
-        (b.center - nearest).magnitude() <= b.radius
+        (b.center - nearest).length() <= b.radius
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SurfaceKind {
+    Floor,
+    Wall,
+    Ceiling,
+    Ramp,
+    Goal,
+    Rim,
+}
+
+impl SurfaceKind {
+    // Classifies a surface from the direction it faces
+    pub fn from_normal(n: &Vec3) -> Self {
+        if n.z >= 0.9 {
+            SurfaceKind::Floor
+        } else if n.z <= -0.9 {
+            SurfaceKind::Ceiling
+        } else if n.z.abs() <= 0.1 {
+            SurfaceKind::Wall
+        } else {
+            SurfaceKind::Ramp
+        }
+    }
+}
+
+// Tags the piece of the arena that a triangle came from
+// The name is shared by every triangle with the same tag, so cloning a surface doesn't copy it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Surface {
+    pub kind: SurfaceKind,
+    pub name: Arc<str>,
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Self {
+            kind: SurfaceKind::Floor,
+            name: Arc::from(""),
+        }
+    }
+}
+
+impl Surface {
+    pub fn new(kind: SurfaceKind, name: &str) -> Self {
+        Self {
+            kind,
+            name: Arc::from(name),
+        }
     }
 }
 
@@ -149,8 +441,257 @@ impl Default for Ray {
     }
 }
 
+// The closest triangle along a ray
+// The normal faces back towards the start of the ray, even if the back of the triangle was hit
+#[derive(Clone, Debug)]
+pub struct RayHit {
+    pub distance: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub tri: Tri,
+}
+
+impl Default for RayHit {
+    fn default() -> Self {
+        Self {
+            distance: 0.,
+            point: Vec3::default(),
+            normal: Vec3::default(),
+            tri: Tri::default(),
+        }
+    }
+}
+
+// The closest point on the arena to some other point
+// The normal is the normal of the closest triangle, the distance is never negative
+#[derive(Clone, Debug)]
+pub struct SurfacePoint {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+    pub surface: Surface,
+}
+
+impl Default for SurfacePoint {
+    fn default() -> Self {
+        Self {
+            point: Vec3::default(),
+            normal: Vec3::default(),
+            distance: 0.,
+            surface: Surface::default(),
+        }
+    }
+}
+
+// One of the points where a shape touches the arena, a list of these is a contact manifold
+// The normal faces out of the arena and depth is how far the shape is past the arena's surface
+#[derive(Clone, Debug)]
+pub struct ContactPoint {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub depth: f32,
+    pub surface: Surface,
+}
+
+impl Default for ContactPoint {
+    fn default() -> Self {
+        Self {
+            point: Vec3::default(),
+            normal: Vec3::default(),
+            depth: 0.,
+            surface: Surface::default(),
+        }
+    }
+}
+
+// A collision with the arena, the direction is the normal of the contact
+#[derive(Clone, Debug)]
+pub struct Contact {
+    pub start: Vec3,
+    pub direction: Vec3,
+    pub surface: Surface,
+}
+
+impl Default for Contact {
+    fn default() -> Self {
+        Self {
+            start: Vec3::default(),
+            direction: Vec3::default(),
+            surface: Surface::default(),
+        }
+    }
+}
+
+// The first contact of a moving sphere with the arena
+// toi ("time of impact") is the fraction of the sweep that the sphere moved before touching, and the point is on the arena
+#[derive(Clone, Debug)]
+pub struct SweepHit {
+    pub toi: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub surface: Surface,
+}
+
+impl Default for SweepHit {
+    fn default() -> Self {
+        Self {
+            toi: 0.,
+            point: Vec3::default(),
+            normal: Vec3::default(),
+            surface: Surface::default(),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Sphere {
     pub center: Vec3,
     pub radius: f32,
 }
+
+// OBB stands for "Oriented Bounding Box"
+// orientation's columns are the box's local x, y and z axes
+#[derive(Clone, Copy, Debug)]
+pub struct Obb {
+    pub center: Vec3,
+    pub half_width: Vec3,
+    pub orientation: Mat3,
+}
+
+impl Default for Obb {
+    fn default() -> Self {
+        Self {
+            center: Vec3::default(),
+            half_width: Vec3::default(),
+            orientation: Mat3::eye(),
+        }
+    }
+}
+
+impl Obb {
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let local = dot(self.orientation.transpose(), p - self.center);
+
+        let clamped = Vec3::new(local.x.clamp(-self.half_width.x, self.half_width.x), local.y.clamp(-self.half_width.y, self.half_width.y), local.z.clamp(-self.half_width.z, self.half_width.z));
+
+        self.center + dot(self.orientation, clamped)
+    }
+
+    // The box's local x, y and z axes in world coordinates
+    pub fn axes(&self) -> [Vec3; 3] {
+        let m = self.orientation.m;
+
+        [Vec3::new(m[0][0], m[1][0], m[2][0]), Vec3::new(m[0][1], m[1][1], m[2][1]), Vec3::new(m[0][2], m[1][2], m[2][2])]
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let [x, y, z] = self.axes();
+        let (x, y, z) = (x * self.half_width.x, y * self.half_width.y, z * self.half_width.z);
+
+        [self.center - x - y - z, self.center + x - y - z, self.center - x + y - z, self.center + x + y - z, self.center - x - y + z, self.center + x - y + z, self.center - x + y + z, self.center + x + y + z]
+    }
+
+    // Half of the length of the box's shadow on the axis
+    fn projected_radius(&self, axis: Vec3) -> f32 {
+        let [x, y, z] = self.axes();
+
+        self.half_width.x * x.dot(axis).abs() + self.half_width.y * y.dot(axis).abs() + self.half_width.z * z.dot(axis).abs()
+    }
+
+    // Separating axis test against another box
+    // The possible separating axes are the axes of both boxes and the cross products of every pair of them
+    pub fn intersect_obb(&self, b: &Obb) -> bool {
+        let a_axes = self.axes();
+        let b_axes = b.axes();
+        let d = b.center - self.center;
+
+        let crosses = a_axes.iter().flat_map(|a| b_axes.iter().map(move |b| a.cross(*b)));
+
+        a_axes.into_iter().chain(b_axes).chain(crosses).all(|axis| {
+            // parallel edges give a zero axis, which can't separate anything
+            axis.dot(axis) < 1e-6 || d.dot(axis).abs() <= self.projected_radius(axis) + b.projected_radius(axis)
+        })
+    }
+
+    pub fn intersect_aabb(&self, b: &Aabb) -> bool {
+        self.intersect_obb(&Obb {
+            center: (b.min + b.max) / 2.,
+            half_width: (b.max - b.min) / 2.,
+            orientation: Mat3::eye(),
+        })
+    }
+
+    pub fn intersect_sphere(&self, b: &Sphere) -> bool {
+        (self.closest_point(b.center) - b.center).length() <= b.radius
+    }
+
+    // Separating axis test against a triangle
+    // The possible separating axes are the triangle's normal, the box's axes and the cross products of the box's axes with the triangle's edges
+    pub fn intersect_tri(&self, tri: &Tri) -> bool {
+        let box_axes = self.axes();
+        let edges = [tri.p[1] - tri.p[0], tri.p[2] - tri.p[1], tri.p[0] - tri.p[2]];
+        let normal = edges[0].cross(edges[1]);
+
+        let crosses = box_axes.iter().flat_map(|a| edges.iter().map(move |e| a.cross(*e)));
+
+        [normal].into_iter().chain(box_axes).chain(crosses).all(|axis| {
+            if axis.dot(axis) < 1e-6 {
+                return true;
+            }
+
+            let c = self.center.dot(axis);
+            let r = self.projected_radius(axis);
+            let (t0, t1, t2) = (tri.p[0].dot(axis), tri.p[1].dot(axis), tri.p[2].dot(axis));
+
+            t0.min(t1).min(t2) <= c + r && t0.max(t1).max(t2) >= c - r
+        })
+    }
+}
+
+// orientation's z axis is the axis of the cylinder
+#[derive(Clone, Copy, Debug)]
+pub struct Cylinder {
+    pub center: Vec3,
+    pub radius: f32,
+    pub half_height: f32,
+    pub orientation: Mat3,
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self {
+            center: Vec3::default(),
+            radius: 0.,
+            half_height: 0.,
+            orientation: Mat3::eye(),
+        }
+    }
+}
+
+impl Cylinder {
+    pub fn axis(&self) -> Vec3 {
+        Vec3::new(self.orientation.m[0][2], self.orientation.m[1][2], self.orientation.m[2][2])
+    }
+
+    // The point of the cylinder that is the furthest in the direction `d`
+    pub fn support(&self, d: Vec3) -> Vec3 {
+        let axis = self.axis();
+        let along = d.dot(axis);
+        let radial = d - axis * along;
+
+        let mut p = self.center + axis * (self.half_height * along.signum());
+
+        if radial.length() > 0.0001 {
+            p += radial.normalize_or_zero() * self.radius;
+        }
+
+        p
+    }
+
+    pub fn bounding_sphere(&self) -> Sphere {
+        Sphere {
+            center: self.center,
+            radius: self.radius.hypot(self.half_height),
+        }
+    }
+}
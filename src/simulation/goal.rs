@@ -0,0 +1,223 @@
+use super::ball::Ball;
+use glam::Vec3;
+
+pub const BLUE_TEAM: u8 = 0;
+pub const ORANGE_TEAM: u8 = 1;
+
+#[derive(Clone, Copy, Debug)]
+pub enum GoalShape {
+    // the ball has to completely cross the plane at `y` between the posts and under the crossbar
+    Plane {
+        y: f32,
+        half_width: f32,
+        height: f32,
+    },
+    // the ball's center has to drop from above the rim to below it, while inside of it
+    Rim {
+        center: Vec3,
+        radius: f32,
+    },
+    // the ball's center has to fall below `z` on this team's half of the field
+    Floor {
+        y_dir: f32,
+        z: f32,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Goal {
+    // the team that defends this goal
+    pub team: u8,
+    pub shape: GoalShape,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GoalEvent {
+    // the team that was scored on
+    pub team: u8,
+    pub slice: usize,
+    pub time: f32,
+    // where the ball crossed the goal line, between this slice and the one before it
+    pub location: Vec3,
+}
+
+impl Goal {
+    const SOCCAR_GOAL_Y: f32 = 5120.;
+    const SOCCAR_GOAL_HALF_WIDTH: f32 = 892.755;
+    const SOCCAR_GOAL_HEIGHT: f32 = 642.775;
+
+    // the inner edge of the rim from initialize_hoops, after it's been scaled and translated, and the rim's lowest point
+    // only the half of the rim in front of the back wall is in the mesh, the test goals_match_meshes checks these against it
+    const HOOPS_RIM_Y: f32 = 2968.71;
+    const HOOPS_RIM_Z: f32 = 310.28;
+    const HOOPS_RIM_RADIUS: f32 = 661.29;
+
+    // the front of the posts, the inside of the posts and the bottom of the crossbar of the throwback goal mesh
+    // like the soccar goal, this is the opening that the ball has to go through, goals_match_meshes checks it against the mesh
+    const THROWBACK_GOAL_Y: f32 = 4884.13;
+    const THROWBACK_GOAL_HALF_WIDTH: f32 = 943.78;
+    const THROWBACK_GOAL_HEIGHT: f32 = 640.04;
+
+    pub fn soccar() -> Vec<Goal> {
+        Goal::planes(Goal::SOCCAR_GOAL_Y, Goal::SOCCAR_GOAL_HALF_WIDTH, Goal::SOCCAR_GOAL_HEIGHT)
+    }
+
+    pub fn hoops() -> Vec<Goal> {
+        vec![
+            Goal {
+                team: BLUE_TEAM,
+                shape: GoalShape::Rim {
+                    center: Vec3::new(0., -Goal::HOOPS_RIM_Y, Goal::HOOPS_RIM_Z),
+                    radius: Goal::HOOPS_RIM_RADIUS,
+                },
+            },
+            Goal {
+                team: ORANGE_TEAM,
+                shape: GoalShape::Rim {
+                    center: Vec3::new(0., Goal::HOOPS_RIM_Y, Goal::HOOPS_RIM_Z),
+                    radius: Goal::HOOPS_RIM_RADIUS,
+                },
+            },
+        ]
+    }
+
+    pub fn dropshot() -> Vec<Goal> {
+        vec![
+            Goal {
+                team: BLUE_TEAM,
+                shape: GoalShape::Floor {
+                    y_dir: -1.,
+                    z: 0.,
+                },
+            },
+            Goal {
+                team: ORANGE_TEAM,
+                shape: GoalShape::Floor {
+                    y_dir: 1.,
+                    z: 0.,
+                },
+            },
+        ]
+    }
+
+    pub fn throwback() -> Vec<Goal> {
+        Goal::planes(Goal::THROWBACK_GOAL_Y, Goal::THROWBACK_GOAL_HALF_WIDTH, Goal::THROWBACK_GOAL_HEIGHT)
+    }
+
+    fn planes(y: f32, half_width: f32, height: f32) -> Vec<Goal> {
+        vec![
+            Goal {
+                team: BLUE_TEAM,
+                shape: GoalShape::Plane {
+                    y: -y,
+                    half_width,
+                    height,
+                },
+            },
+            Goal {
+                team: ORANGE_TEAM,
+                shape: GoalShape::Plane {
+                    y,
+                    half_width,
+                    height,
+                },
+            },
+        ]
+    }
+
+    pub fn is_scored(&self, ball: &Ball) -> bool {
+        let p = ball.location;
+
+        match self.shape {
+            GoalShape::Plane {
+                y,
+                half_width,
+                height,
+            } => p.x.abs() < half_width && p.z < height && p.y * y.signum() > y.abs() + ball.radius,
+            GoalShape::Rim {
+                center,
+                radius,
+            } => p.z < center.z && (p.x - center.x).hypot(p.y - center.y) < radius,
+            GoalShape::Floor {
+                y_dir,
+                z,
+            } => p.y * y_dir > 0. && p.z < z - ball.radius,
+        }
+    }
+
+    // How far the ball is past the line that it has to cross to score, negative if it hasn't crossed it
+    fn depth(&self, ball: &Ball) -> f32 {
+        let p = ball.location;
+
+        match self.shape {
+            GoalShape::Plane {
+                y,
+                ..
+            } => p.y * y.signum() - (y.abs() + ball.radius),
+            GoalShape::Rim {
+                center,
+                ..
+            } => center.z - p.z,
+            GoalShape::Floor {
+                z,
+                ..
+            } => z - ball.radius - p.z,
+        }
+    }
+
+    // Where the ball crossed the goal line on its way from `previous` to `ball`
+    fn crossing(&self, previous: &Ball, ball: &Ball) -> Vec3 {
+        let before = self.depth(previous);
+        let after = self.depth(ball);
+
+        // the ball might have already been past the line, and only scored by moving in between the posts
+        let t = if after > before {
+            (-before / (after - before)).clamp(0., 1.)
+        } else {
+            1.
+        };
+
+        previous.location + (ball.location - previous.location) * t
+    }
+
+    // Whether the ball scores on its way from `previous` to `ball`
+    fn enters(&self, previous: &Ball, ball: &Ball, was_scored: bool) -> bool {
+        match self.shape {
+            // the ball has to come down through the rim, a ball that ends up under it from the side didn't score
+            GoalShape::Rim {
+                center,
+                ..
+            } => self.is_scored(ball) && previous.location.z >= center.z,
+            _ => self.is_scored(ball) && !was_scored,
+        }
+    }
+
+    // Finds every slice where the ball goes from not being in a goal to being in it
+    // `previous` is the state of the ball just before the first slice
+    pub fn find_events(goals: &[Goal], previous: &Ball, slices: &[Ball], first_slice: usize) -> Vec<GoalEvent> {
+        let mut events = Vec::new();
+        let mut was_scored: Vec<bool> = goals.iter().map(|goal| goal.is_scored(previous)).collect();
+        let mut previous = previous;
+
+        for (i, ball) in slices.iter().enumerate() {
+            for (goal, was_scored) in goals.iter().zip(was_scored.iter_mut()) {
+                let is_scored = goal.is_scored(ball);
+
+                if goal.enters(previous, ball, *was_scored) {
+                    events.push(GoalEvent {
+                        team: goal.team,
+                        slice: first_slice + i,
+                        time: ball.time,
+                        location: goal.crossing(previous, ball),
+                    });
+                }
+
+                *was_scored = is_scored;
+            }
+
+            previous = ball;
+        }
+
+        events
+    }
+}
@@ -0,0 +1,160 @@
+use super::goal::BLUE_TEAM;
+use glam::Vec3;
+
+// The state of a heatseeker ball, it's part of every slice because it changes during the prediction
+// Constants are from RocketSim's reverse engineering of the game
+#[derive(Clone, Copy, Debug)]
+pub struct Heatseeker {
+    // 1 if the ball is seeking the orange goal, -1 for the blue goal, 0 if it hasn't been touched yet
+    pub y_target_dir: f32,
+    pub target_speed: f32,
+    pub time_since_hit: f32,
+}
+
+impl Default for Heatseeker {
+    fn default() -> Self {
+        Self {
+            y_target_dir: 0.,
+            target_speed: Heatseeker::INITIAL_TARGET_SPEED,
+            time_since_hit: 0.,
+        }
+    }
+}
+
+impl Heatseeker {
+    const INITIAL_TARGET_SPEED: f32 = 2900.;
+    const TARGET_SPEED_INCREMENT: f32 = 85.;
+    const MIN_TIME_BETWEEN_HITS: f32 = 0.1;
+    pub const MAX_SPEED: f32 = 4600.;
+
+    const TARGET_Y: f32 = 5120.;
+    const TARGET_Z: f32 = 320.;
+
+    const HORIZONTAL_BLEND: f32 = 1.45;
+    const VERTICAL_BLEND: f32 = 0.78;
+    const SPEED_BLEND: f32 = 0.3;
+    const MAX_TURN_PITCH: f32 = 7000. * std::f32::consts::PI / 32768.;
+
+    const WALL_BOUNCE_CHANGE_Y_THRESH: f32 = 300.;
+    const WALL_BOUNCE_CHANGE_Y_NORMAL: f32 = 0.5;
+    const ARENA_EXTENT_Y: f32 = 5120.;
+
+    fn yaw_pitch(v: Vec3) -> (f32, f32) {
+        (v.y.atan2(v.x), v.z.atan2(v.x.hypot(v.y)))
+    }
+
+    fn wrap_angle(angle: f32) -> f32 {
+        (angle + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+    }
+
+    // Turns the ball's velocity towards the goal that it's seeking
+    pub fn seek(&mut self, location: Vec3, velocity: Vec3, dt: f32) -> Vec3 {
+        if self.y_target_dir == 0. {
+            return velocity;
+        }
+
+        self.time_since_hit += dt;
+
+        let speed = velocity.length();
+        if speed == 0. {
+            return velocity;
+        }
+
+        let target = Vec3::new(0., Heatseeker::TARGET_Y * self.y_target_dir, Heatseeker::TARGET_Z);
+
+        let (vel_yaw, vel_pitch) = Heatseeker::yaw_pitch(velocity);
+        let (target_yaw, target_pitch) = Heatseeker::yaw_pitch(target - location);
+
+        // the ball turns faster the closer it is to its max speed
+        let blend = self.target_speed / Heatseeker::MAX_SPEED * dt;
+
+        let yaw = vel_yaw + Heatseeker::wrap_angle(target_yaw - vel_yaw) * Heatseeker::HORIZONTAL_BLEND * blend;
+        let pitch = (vel_pitch + (target_pitch - vel_pitch) * Heatseeker::VERTICAL_BLEND * blend).clamp(-Heatseeker::MAX_TURN_PITCH, Heatseeker::MAX_TURN_PITCH);
+
+        let new_speed = speed + (self.target_speed - speed) * Heatseeker::SPEED_BLEND;
+
+        Vec3::new(pitch.cos() * yaw.cos(), pitch.cos() * yaw.sin(), pitch.sin()) * new_speed
+    }
+
+    // A car touch makes the ball seek the other team's goal and, after the first touch, speeds it up
+    pub fn on_touch(&mut self, team: u8) {
+        if self.y_target_dir != 0. && self.time_since_hit > Heatseeker::MIN_TIME_BETWEEN_HITS {
+            self.speed_up();
+        }
+
+        self.y_target_dir = if team == BLUE_TEAM {
+            1.
+        } else {
+            -1.
+        };
+        self.time_since_hit = 0.;
+    }
+
+    // Bouncing off of the back wall that the ball is seeking sends it back towards the other goal
+    pub fn on_world_contact(&mut self, location: Vec3, normal: Vec3) {
+        if self.y_target_dir == 0. {
+            return;
+        }
+
+        let rel_normal_y = normal.y * self.y_target_dir;
+        let rel_y = location.y * self.y_target_dir;
+
+        if rel_normal_y <= -Heatseeker::WALL_BOUNCE_CHANGE_Y_NORMAL && rel_y >= Heatseeker::ARENA_EXTENT_Y - Heatseeker::WALL_BOUNCE_CHANGE_Y_THRESH {
+            self.speed_up();
+            self.y_target_dir *= -1.;
+        }
+    }
+
+    fn speed_up(&mut self) {
+        self.target_speed = (self.target_speed + Heatseeker::TARGET_SPEED_INCREMENT).min(Heatseeker::MAX_SPEED);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::simulation::goal::ORANGE_TEAM;
+
+    #[test]
+    fn check_touches() {
+        let mut heatseeker = Heatseeker::default();
+
+        // the first touch only picks a target
+        heatseeker.on_touch(BLUE_TEAM);
+        assert_eq!(heatseeker.y_target_dir, 1.);
+        assert_eq!(heatseeker.target_speed, Heatseeker::INITIAL_TARGET_SPEED);
+
+        // touches that are too close together don't speed the ball up
+        heatseeker.seek(Vec3::default(), Vec3::new(0., 1000., 0.), 0.05);
+        heatseeker.on_touch(ORANGE_TEAM);
+        assert_eq!(heatseeker.y_target_dir, -1.);
+        assert_eq!(heatseeker.target_speed, Heatseeker::INITIAL_TARGET_SPEED);
+
+        heatseeker.seek(Vec3::default(), Vec3::new(0., -1000., 0.), 0.5);
+        heatseeker.on_touch(BLUE_TEAM);
+        assert_eq!(heatseeker.y_target_dir, 1.);
+        assert_eq!(heatseeker.target_speed, Heatseeker::INITIAL_TARGET_SPEED + Heatseeker::TARGET_SPEED_INCREMENT);
+
+        for _ in 0..100 {
+            heatseeker.speed_up();
+        }
+        assert_eq!(heatseeker.target_speed, Heatseeker::MAX_SPEED);
+    }
+
+    #[test]
+    fn check_back_wall_bounce() {
+        let mut heatseeker = Heatseeker {
+            y_target_dir: 1.,
+            ..Default::default()
+        };
+
+        // side walls and the other team's back wall don't change the target
+        heatseeker.on_world_contact(Vec3::new(4000., 5000., 300.), Vec3::new(-1., 0., 0.));
+        heatseeker.on_world_contact(Vec3::new(2000., -5000., 300.), Vec3::new(0., 1., 0.));
+        assert_eq!(heatseeker.y_target_dir, 1.);
+
+        heatseeker.on_world_contact(Vec3::new(2000., 5000., 300.), Vec3::new(0., -1., 0.));
+        assert_eq!(heatseeker.y_target_dir, -1.);
+        assert_eq!(heatseeker.target_speed, Heatseeker::INITIAL_TARGET_SPEED + Heatseeker::TARGET_SPEED_INCREMENT);
+    }
+}
@@ -1,12 +1,20 @@
-use super::geometry::Tri;
+use super::geometry::{Surface, SurfaceKind, Tri};
+use crate::error::MeshError;
 use crate::linear_algebra::mat::Mat3;
 use crate::linear_algebra::math::dot;
-use vvec3::Vec3;
+use byteorder::{ByteOrder, LittleEndian};
+use glam::Vec3;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct Mesh {
     pub ids: Vec<i32>,
     pub vertices: Vec<f32>,
+    // one per triangle, untagged meshes are classified by the direction each triangle faces
+    pub surfaces: Vec<Surface>,
 }
 
 impl Default for Mesh {
@@ -14,11 +22,180 @@ impl Default for Mesh {
         Self {
             ids: Vec::new(),
             vertices: Vec::new(),
+            surfaces: Vec::new(),
         }
     }
 }
 
 impl Mesh {
+    // Reads a mesh in the same format as the meshes in the assets folder
+    // `ids` is a list of little-endian i32s, 3 per triangle
+    // `vertices` is a list of little-endian f32s, 3 per vertex
+    pub fn from_bin(mut ids_dat: impl Read, mut vertices_dat: impl Read) -> Result<Self, MeshError> {
+        let mut ids_bytes = Vec::new();
+        ids_dat.read_to_end(&mut ids_bytes)?;
+
+        if !ids_bytes.len().is_multiple_of(12) {
+            return Err(MeshError::Truncated {
+                file: "ids",
+                len: ids_bytes.len(),
+            });
+        }
+
+        let mut vertices_bytes = Vec::new();
+        vertices_dat.read_to_end(&mut vertices_bytes)?;
+
+        if !vertices_bytes.len().is_multiple_of(12) {
+            return Err(MeshError::Truncated {
+                file: "vertices",
+                len: vertices_bytes.len(),
+            });
+        }
+
+        let mut ids: Vec<i32> = vec![0; ids_bytes.len() / 4];
+        LittleEndian::read_i32_into(&ids_bytes, &mut ids);
+
+        let mut vertices: Vec<f32> = vec![0.; vertices_bytes.len() / 4];
+        LittleEndian::read_f32_into(&vertices_bytes, &mut vertices);
+
+        let mesh = Self {
+            ids,
+            vertices,
+            surfaces: Vec::new(),
+        };
+
+        mesh.validate()?;
+
+        Ok(mesh)
+    }
+
+    pub fn from_bin_files(ids_path: impl AsRef<Path>, vertices_path: impl AsRef<Path>) -> Result<Self, MeshError> {
+        Mesh::from_bin(BufReader::new(File::open(ids_path)?), BufReader::new(File::open(vertices_path)?))
+    }
+
+    // Reads the vertices and faces of a Wavefront OBJ file, everything else is ignored
+    // Faces with more than 3 vertices are split into triangle fans
+    // The triangles are named after the object or group (`o` or `g`) that they're in, faces before the first one have no name
+    pub fn from_obj(reader: impl BufRead) -> Result<Self, MeshError> {
+        let mut ids: Vec<i32> = Vec::new();
+        let mut vertices: Vec<f32> = Vec::new();
+        let mut names: Vec<Arc<str>> = Vec::new();
+        let mut name: Arc<str> = Arc::from("");
+        let mut has_names = false;
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_number = i + 1;
+            let mut parts = line.split_whitespace();
+
+            match parts.next() {
+                Some("v") => {
+                    for _ in 0..3 {
+                        vertices.push(parse_obj_value(parts.next(), line_number)?);
+                    }
+                }
+                Some("f") => {
+                    let num_vertices = (vertices.len() / 3) as i32;
+                    let face: Vec<i32> = parts.map(|part| parse_obj_index(part, num_vertices, line_number)).collect::<Result<_, _>>()?;
+
+                    if face.len() < 3 {
+                        return Err(MeshError::Parse {
+                            line: line_number,
+                            message: String::from("face with less than 3 vertices"),
+                        });
+                    }
+
+                    for i in 1..face.len() - 1 {
+                        ids.push(face[0]);
+                        ids.push(face[i]);
+                        ids.push(face[i + 1]);
+                        names.push(name.clone());
+                    }
+                }
+                Some("o" | "g") => {
+                    name = Arc::from(parts.collect::<Vec<_>>().join(" "));
+                    has_names = true;
+                }
+                _ => {}
+            }
+        }
+
+        let mut mesh = Self {
+            ids,
+            vertices,
+            surfaces: Vec::new(),
+        };
+
+        mesh.validate()?;
+
+        // without any names the triangles are left untagged, like the meshes in the assets folder
+        if has_names {
+            mesh.surfaces = mesh
+                .to_untagged_triangles()
+                .iter()
+                .zip(names)
+                .map(|(tri, name)| Surface {
+                    kind: SurfaceKind::from_normal(&tri.unit_normal()),
+                    name,
+                })
+                .collect();
+        }
+
+        Ok(mesh)
+    }
+
+    pub fn from_obj_file(path: impl AsRef<Path>) -> Result<Self, MeshError> {
+        Mesh::from_obj(BufReader::new(File::open(path)?))
+    }
+
+    // Checks that the mesh can be safely turned into triangles and used for collisions
+    pub fn validate(&self) -> Result<(), MeshError> {
+        if !self.ids.len().is_multiple_of(3) {
+            return Err(MeshError::IncompleteTriangle {
+                ids: self.ids.len(),
+            });
+        }
+
+        if !self.vertices.len().is_multiple_of(3) {
+            return Err(MeshError::Truncated {
+                file: "vertices",
+                len: self.vertices.len() * 4,
+            });
+        }
+
+        if self.ids.is_empty() {
+            return Err(MeshError::Empty);
+        }
+
+        if let Some(i) = self.vertices.iter().position(|v| !v.is_finite()) {
+            return Err(MeshError::NanVertex {
+                vertex: i / 3,
+            });
+        }
+
+        let num_vertices = self.vertices.len() / 3;
+
+        if let Some(i) = self.ids.iter().position(|&id| id < 0 || id as usize >= num_vertices) {
+            return Err(MeshError::IndexOutOfBounds {
+                triangle: i / 3,
+                index: self.ids[i],
+                num_vertices,
+            });
+        }
+
+        for (i, tri) in self.to_untagged_triangles().iter().enumerate() {
+            let n = (tri.p[1] - tri.p[0]).cross(tri.p[2] - tri.p[0]);
+
+            if n.length() <= 0. {
+                return Err(MeshError::DegenerateTriangle {
+                    triangle: i,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn from(other_meshes: Vec<&Self>) -> Self {
         let mut id_offset = 0;
 
@@ -32,6 +209,7 @@ impl Mesh {
 
         let mut ids: Vec<i32> = Vec::with_capacity(n_ids);
         let mut vertices: Vec<f32> = Vec::with_capacity(n_vertices);
+        let mut surfaces: Vec<Surface> = Vec::with_capacity(n_ids / 3);
 
         for m in other_meshes {
             for id in &m.ids {
@@ -42,47 +220,55 @@ impl Mesh {
                 vertices.push(*vertex);
             }
 
+            surfaces.extend(m.surfaces());
+
             id_offset += (m.vertices.len() / 3) as i32;
         }
 
-        // remove duplicate vertices and their ids
-        // let mut i = 0;
-        // let mut dups = 0;
-
-        // loop {
-        //     let id = (ids[i] * 3) as usize;
-        //     match vertices[..id].iter().position(|&x| x == vertices[id + 0]) {
-        //         Some(j) => {
-        //             if vertices[j + 1] == vertices[id + 1] && vertices[j + 2] == vertices[id + 2] {
-        //                 // dups += 1;
-        //                 n_ids -= 3;
-
-        //                 vertices.remove(id + 0);
-        //                 vertices.remove(id + 1);
-        //                 vertices.remove(id + 2);
-
-        //                 ids.remove(i + 0);
-        //                 ids.remove(i + 1);
-        //                 ids.remove(i + 2);
-
-        //                 for l in i..n_ids {
-        //                     ids[l] -= 3;
-        //                 }
-        //             }
-        //         }
-        //         None => {}
-        //     };
-
-        //     i += 3;
-        //     if i >= n_ids {
-        //         break;
-        //     }
-        // }
-        // println!("Removed {} duplicate vertices!", dups);
-
         Self {
             ids,
             vertices,
+            surfaces,
+        }
+    }
+
+    // Tags every triangle in the mesh as the same kind of surface
+    pub fn tag(&self, name: &str, kind: SurfaceKind) -> Self {
+        Self {
+            ids: self.ids.clone(),
+            vertices: self.vertices.clone(),
+            surfaces: vec![Surface::new(kind, name); self.ids.len() / 3],
+        }
+    }
+
+    // Tags every triangle in the mesh with the same name, classifying each one by the direction it faces
+    pub fn tag_by_normal(&self, name: &str) -> Self {
+        self.tag_with(name, |tri| SurfaceKind::from_normal(&tri.unit_normal()))
+    }
+
+    // Tags every triangle in the mesh with the same name, classifying each one with `kind`
+    pub fn tag_with(&self, name: &str, kind: impl Fn(&Tri) -> SurfaceKind) -> Self {
+        let name: Arc<str> = Arc::from(name);
+
+        Self {
+            ids: self.ids.clone(),
+            vertices: self.vertices.clone(),
+            surfaces: self
+                .to_untagged_triangles()
+                .iter()
+                .map(|tri| Surface {
+                    kind: kind(tri),
+                    name: name.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn surfaces(&self) -> Vec<Surface> {
+        if self.surfaces.len() == self.ids.len() / 3 {
+            self.surfaces.clone()
+        } else {
+            self.tag_by_normal("").surfaces
         }
     }
 
@@ -115,6 +301,7 @@ impl Mesh {
         Mesh {
             ids,
             vertices,
+            surfaces: self.surfaces.clone(),
         }
     }
 
@@ -133,11 +320,22 @@ impl Mesh {
         Self {
             ids,
             vertices,
+            surfaces: self.surfaces.clone(),
         }
     }
 
-    #[rustfmt::skip]
     pub fn to_triangles(&self) -> Vec<Tri> {
+        let mut triangles = self.to_untagged_triangles();
+
+        for (tri, surface) in triangles.iter_mut().zip(self.surfaces()) {
+            tri.surface = surface;
+        }
+
+        triangles
+    }
+
+    #[rustfmt::skip]
+    fn to_untagged_triangles(&self) -> Vec<Tri> {
         let n = self.ids.len() / 3;
         let mut triangles: Vec<Tri> = Vec::with_capacity(n);
 
@@ -154,3 +352,33 @@ impl Mesh {
         triangles
     }
 }
+
+fn parse_obj_value(part: Option<&str>, line: usize) -> Result<f32, MeshError> {
+    match part.map(str::parse::<f32>) {
+        Some(Ok(value)) => Ok(value),
+        _ => Err(MeshError::Parse {
+            line,
+            message: String::from("vertex without 3 coordinates"),
+        }),
+    }
+}
+
+// OBJ indices start at 1, and negative indices count back from the latest vertex
+// Only the vertex index is used from faces like "1/2/3"
+fn parse_obj_index(part: &str, num_vertices: i32, line: usize) -> Result<i32, MeshError> {
+    let index = match part.split('/').next().map(str::parse::<i32>) {
+        Some(Ok(index)) => index,
+        _ => {
+            return Err(MeshError::Parse {
+                line,
+                message: format!("invalid face index: {}", part),
+            })
+        }
+    };
+
+    Ok(if index < 0 {
+        num_vertices + index
+    } else {
+        index - 1
+    })
+}
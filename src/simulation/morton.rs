@@ -1,5 +1,5 @@
 use super::geometry::Aabb;
-use vvec3::Vec3;
+use glam::Vec3;
 
 pub struct Morton {
     pub offset: Vec3,
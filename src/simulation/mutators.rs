@@ -0,0 +1,175 @@
+use super::ball::{Ball, BallPhysicsParams};
+use super::game::Game;
+use super::puck::Puck;
+use glam::Vec3;
+
+// The in-game mutators that change how the ball moves
+// Gravity matches the in-game values, the rest are multipliers on the standard ball and are approximate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BallType {
+    Default,
+    // the hoops ball
+    Basketball,
+    // the snow day puck, predicted with Puck::get_puck_prediction_struct
+    Puck,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BallMaxSpeed {
+    Default,
+    Slow,
+    Fast,
+    SuperFast,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BallWeight {
+    Default,
+    SuperLight,
+    Light,
+    Heavy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BallSize {
+    Default,
+    Small,
+    Medium,
+    Large,
+    Gigantic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BallBounciness {
+    Default,
+    Low,
+    Lowish,
+    High,
+    SuperHigh,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gravity {
+    Default,
+    Low,
+    High,
+    SuperHigh,
+    Reverse,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Mutators {
+    pub ball_type: BallType,
+    pub ball_max_speed: BallMaxSpeed,
+    pub ball_weight: BallWeight,
+    pub ball_size: BallSize,
+    pub ball_bounciness: BallBounciness,
+    pub gravity: Gravity,
+}
+
+impl Default for Mutators {
+    fn default() -> Self {
+        Self {
+            ball_type: BallType::Default,
+            ball_max_speed: BallMaxSpeed::Default,
+            ball_weight: BallWeight::Default,
+            ball_size: BallSize::Default,
+            ball_bounciness: BallBounciness::Default,
+            gravity: Gravity::Default,
+        }
+    }
+}
+
+impl BallMaxSpeed {
+    pub fn multiplier(self) -> f32 {
+        match self {
+            BallMaxSpeed::Default => 1.,
+            BallMaxSpeed::Slow => 0.5,
+            BallMaxSpeed::Fast => 1.5,
+            BallMaxSpeed::SuperFast => 2.,
+        }
+    }
+}
+
+impl BallWeight {
+    pub fn multiplier(self) -> f32 {
+        match self {
+            BallWeight::Default => 1.,
+            BallWeight::SuperLight => 0.25,
+            BallWeight::Light => 0.5,
+            BallWeight::Heavy => 2.,
+        }
+    }
+}
+
+impl BallSize {
+    pub fn multiplier(self) -> f32 {
+        match self {
+            BallSize::Default => 1.,
+            BallSize::Small => 0.5,
+            BallSize::Medium => 1.5,
+            BallSize::Large => 2.,
+            BallSize::Gigantic => 3.,
+        }
+    }
+}
+
+impl BallBounciness {
+    pub fn multiplier(self) -> f32 {
+        match self {
+            BallBounciness::Default => 1.,
+            BallBounciness::Low => 0.5,
+            BallBounciness::Lowish => 0.75,
+            BallBounciness::High => 1.25,
+            BallBounciness::SuperHigh => 1.5,
+        }
+    }
+}
+
+impl Gravity {
+    pub fn z(self) -> f32 {
+        match self {
+            Gravity::Default => -650.,
+            Gravity::Low => -325.,
+            Gravity::High => -1137.5,
+            Gravity::SuperHigh => -3250.,
+            Gravity::Reverse => 650.,
+        }
+    }
+}
+
+impl Mutators {
+    pub fn ball_physics(&self) -> BallPhysicsParams {
+        let default = BallPhysicsParams::default();
+
+        BallPhysicsParams {
+            restitution: default.restitution * self.ball_bounciness.multiplier(),
+            v_max: default.v_max * self.ball_max_speed.multiplier(),
+            m: default.m * self.ball_weight.multiplier(),
+            ..default
+        }
+    }
+
+    // Applies the mutators to a freshly loaded game, and places the ball where it starts a kickoff
+    // These are the mutators of a private soccar match, so the size is applied to the soccar ball unless the ball type is changed
+    // Everything is derived from the unmutated ball, so applying the same mutators again doesn't change the game
+    pub fn apply(&self, game: &mut Game) {
+        game.ball_physics = self.ball_physics();
+        game.gravity = Vec3::new(0., 0., self.gravity.z());
+
+        let base = match self.ball_type {
+            BallType::Basketball => Ball::initialize_hoops(),
+            BallType::Default | BallType::Puck => Ball::initialize_soccar(),
+        };
+
+        if self.ball_type == BallType::Puck {
+            game.puck = Some(Puck::initialize_snowday());
+        }
+
+        let scale = self.ball_size.multiplier();
+        game.ball.radius = base.radius * scale;
+        game.ball.collision_radius = base.collision_radius * scale;
+        game.ball.location.z = base.location.z * scale;
+        game.ball.calculate_moi_with_mass(game.ball_physics.m);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::simulation::ball::Ball;
+use crate::simulation::game::Game;
+use glam::Vec3;
+
+// The number of slices in RLBot's BallPrediction
+pub const RLBOT_MAX_SLICES: usize = 360;
+
+// The types below have the same layout as RLBot's BallPrediction, so a FixedBallPrediction<RLBOT_MAX_SLICES> can be handed to framework code as is
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Default for Vector3 {
+    fn default() -> Self {
+        Self {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        }
+    }
+}
+
+impl From<Vec3> for Vector3 {
+    fn from(v: Vec3) -> Self {
+        Self {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl From<Vector3> for Vec3 {
+    fn from(v: Vector3) -> Self {
+        Vec3::new(v.x, v.y, v.z)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rotator {
+    pub pitch: f32,
+    pub yaw: f32,
+    pub roll: f32,
+}
+
+impl Default for Rotator {
+    fn default() -> Self {
+        Self {
+            pitch: 0.,
+            yaw: 0.,
+            roll: 0.,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Physics {
+    pub location: Vector3,
+    // the ball's rotation isn't simulated, so this is always zero
+    pub rotation: Rotator,
+    pub velocity: Vector3,
+    pub angular_velocity: Vector3,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self {
+            location: Vector3::default(),
+            rotation: Rotator::default(),
+            velocity: Vector3::default(),
+            angular_velocity: Vector3::default(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PredictionSlice {
+    pub physics: Physics,
+    pub game_seconds: f32,
+}
+
+impl Default for PredictionSlice {
+    fn default() -> Self {
+        Self {
+            physics: Physics::default(),
+            game_seconds: 0.,
+        }
+    }
+}
+
+impl From<&Ball> for PredictionSlice {
+    fn from(ball: &Ball) -> Self {
+        Self {
+            physics: Physics {
+                location: ball.location.into(),
+                rotation: Rotator::default(),
+                velocity: ball.velocity.into(),
+                angular_velocity: ball.angular_velocity.into(),
+            },
+            game_seconds: ball.time,
+        }
+    }
+}
+
+// A ball prediction with room for N slices that can be refilled every tick without allocating, apart from the copy of the tiles in dropshot
+// Only the ball's path is predicted, use Ball::get_ball_prediction_struct for the goals, contacts and dropshot tile damage
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FixedBallPrediction<const N: usize> {
+    pub slices: [PredictionSlice; N],
+    // an i32 to match RLBot's layout
+    pub num_slices: i32,
+}
+
+impl<const N: usize> Default for FixedBallPrediction<N> {
+    fn default() -> Self {
+        Self {
+            slices: [PredictionSlice::default(); N],
+            num_slices: 0,
+        }
+    }
+}
+
+impl<const N: usize> FixedBallPrediction<N> {
+    // The slices that have been filled
+    pub fn slices(&self) -> &[PredictionSlice] {
+        &self.slices[..self.num_slices as usize]
+    }
+
+    // Fills every slice, one simulation step (1/120 of a second) apart
+    pub fn fill(&mut self, game: &mut Game) {
+        self.fill_every(game, 1);
+    }
+
+    // Fills every slice, `steps_per_slice` simulation steps apart
+    // RLBot's own prediction is 360 slices that are 2 steps apart
+    pub fn fill_every(&mut self, game: &mut Game, steps_per_slice: usize) {
+        fill_slices(game, &mut self.slices, steps_per_slice);
+        self.num_slices = N as i32;
+    }
+}
+
+// The same size as RLBot's BallPrediction
+pub type RLBotBallPrediction = FixedBallPrediction<RLBOT_MAX_SLICES>;
+
+// Fills every slice, `steps_per_slice` simulation steps apart, and then puts the ball back where it started
+// Dropshot tiles are opened the same way as in Ball::get_ball_prediction_struct, on a copy of them that's thrown away afterwards
+pub fn fill_slices(game: &mut Game, slices: &mut [PredictionSlice], steps_per_slice: usize) {
+    let start = game.ball;
+    let start_tiles = game.dropshot_tiles.clone();
+    let mut tile_damage = Vec::new();
+    let mut step = 0;
+
+    for slice in slices {
+        for _ in 0..steps_per_slice {
+            Ball::step_slice(game, step, &mut None, &mut tile_damage);
+            step += 1;
+        }
+
+        *slice = PredictionSlice::from(&game.ball);
+    }
+
+    game.ball = start;
+    game.dropshot_tiles = start_tiles;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::mem::{offset_of, size_of};
+
+    #[test]
+    fn rlbot_layout() {
+        assert_eq!(size_of::<Vector3>(), 12);
+        assert_eq!(size_of::<Rotator>(), 12);
+
+        assert_eq!(size_of::<Physics>(), 48);
+        assert_eq!(offset_of!(Physics, location), 0);
+        assert_eq!(offset_of!(Physics, rotation), 12);
+        assert_eq!(offset_of!(Physics, velocity), 24);
+        assert_eq!(offset_of!(Physics, angular_velocity), 36);
+
+        assert_eq!(size_of::<PredictionSlice>(), 52);
+        assert_eq!(offset_of!(PredictionSlice, game_seconds), 48);
+
+        assert_eq!(size_of::<FixedBallPrediction<RLBOT_MAX_SLICES>>(), 52 * RLBOT_MAX_SLICES + 4);
+        assert_eq!(offset_of!(FixedBallPrediction<RLBOT_MAX_SLICES>, num_slices), 52 * RLBOT_MAX_SLICES);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::linear_algebra::mat::Mat3;
+use crate::simulation::game::Game;
+use crate::simulation::geometry::{Contact, Cylinder};
+use glam::Vec3;
+
+// The Snow Day puck
+// It never tumbles, it only spins around its own axis and lies flat on whatever it's sliding on
+#[derive(Clone, Copy, Debug)]
+pub struct Puck {
+    pub time: f32,
+    pub location: Vec3,
+    pub velocity: Vec3,
+    pub angular_velocity: Vec3,
+    // columns are the forward, left and up vectors of the puck, up is the puck's axis
+    pub orientation: Mat3,
+    pub radius: f32,
+    pub half_height: f32,
+}
+
+impl Default for Puck {
+    fn default() -> Self {
+        Self {
+            time: 0.,
+            location: Vec3::default(),
+            velocity: Vec3::default(),
+            angular_velocity: Vec3::default(),
+            orientation: Mat3::eye(),
+            radius: 0.,
+            half_height: 0.,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PuckPrediction {
+    pub num_slices: usize,
+    pub slices: Vec<Puck>,
+}
+
+impl Default for PuckPrediction {
+    fn default() -> Self {
+        Self {
+            num_slices: 0,
+            slices: Vec::new(),
+        }
+    }
+}
+
+impl Puck {
+    // dimensions and physics constants are from RocketSim
+    const RADIUS: f32 = 114.25;
+    const HALF_HEIGHT: f32 = 31.25;
+
+    const RESTITUTION: f32 = 0.3;
+    const DRAG: f32 = -0.0305;
+    const MU: f32 = 0.1;
+
+    const V_MAX: f32 = 4000.;
+    const W_MAX: f32 = 6.;
+
+    const M: f32 = 50.;
+
+    const SIMULATION_DT: f32 = 1. / 120.;
+    const STANDARD_NUM_SLICES: usize = 720;
+
+    pub fn initialize_snowday() -> Self {
+        Puck {
+            location: Vec3::new(0., 0., Puck::HALF_HEIGHT),
+            radius: Puck::RADIUS,
+            half_height: Puck::HALF_HEIGHT,
+            ..Default::default()
+        }
+    }
+
+    pub fn update(&mut self, time: f32, location: Vec3, velocity: Vec3, angular_velocity: Vec3, orientation: Mat3) {
+        self.time = time;
+        self.location = location;
+        self.velocity = velocity;
+        self.angular_velocity = angular_velocity;
+        self.orientation = orientation;
+    }
+
+    pub fn up(&self) -> Vec3 {
+        Vec3::new(self.orientation.m[0][2], self.orientation.m[1][2], self.orientation.m[2][2])
+    }
+
+    // the moment of inertia around the puck's axis
+    fn moi(&self) -> f32 {
+        0.5 * Puck::M * self.radius * self.radius
+    }
+
+    pub fn hitbox(&self) -> Cylinder {
+        Cylinder {
+            center: self.location,
+            radius: self.radius,
+            half_height: self.half_height,
+            orientation: self.orientation,
+        }
+    }
+
+    // Keeps the puck's forward direction as close as possible to what it was, while making `up` the puck's axis
+    fn lie_flat(&mut self, up: Vec3) {
+        let f = Vec3::new(self.orientation.m[0][0], self.orientation.m[1][0], self.orientation.m[2][0]);
+        let mut forward = f - up * f.dot(up);
+
+        if forward.length() < 0.0001 {
+            let l = Vec3::new(self.orientation.m[0][1], self.orientation.m[1][1], self.orientation.m[2][1]);
+            forward = up.cross(l);
+        }
+
+        self.orientation = from_columns(forward.normalize_or_zero(), up.cross(forward.normalize_or_zero()), up);
+    }
+
+    fn spin(&mut self, angle: f32) {
+        let (s, c) = angle.sin_cos();
+        let f = Vec3::new(self.orientation.m[0][0], self.orientation.m[1][0], self.orientation.m[2][0]);
+        let l = Vec3::new(self.orientation.m[0][1], self.orientation.m[1][1], self.orientation.m[2][1]);
+
+        self.orientation = from_columns(f * c + l * s, l * c - f * s, self.up());
+    }
+
+    // Does nothing if the game doesn't have a puck
+    pub fn step(game: &mut Game, dt: f32) -> Option<Contact> {
+        let puck = game.puck.as_mut()?;
+        let contact = game.arena.collision_mesh.collide_cylinder(&puck.hitbox());
+
+        match &contact {
+            Some(contact) => {
+                let n = contact.direction;
+                let loc = contact.start - puck.location;
+
+                let v_perp = n * puck.velocity.dot(n).min(0.);
+                let v_para = puck.velocity - v_perp - loc.cross(puck.angular_velocity);
+
+                let ratio = v_perp.length() / v_para.length().max(0.0001);
+
+                let j_perp = v_perp * (-(1. + Puck::RESTITUTION) * Puck::M);
+                let j_para = -(Puck::MU * ratio).min(1.) * Puck::M * v_para;
+
+                // friction can only spin the puck around its axis
+                let up = puck.up();
+                puck.angular_velocity += up * (loc.cross(j_para).dot(up) / puck.moi());
+                puck.velocity += ((j_perp + j_para) / Puck::M) + puck.velocity * (Puck::DRAG * dt);
+                puck.location += puck.velocity * dt;
+
+                // sliding on one of its faces, not bouncing off of its edge
+                if n.dot(up).abs() > 0.7 {
+                    puck.lie_flat(n * n.dot(up).signum());
+                    let up = puck.up();
+                    puck.angular_velocity = up * puck.angular_velocity.dot(up);
+                }
+
+                let penetration = (contact.start - puck.hitbox().support(n * -1.)).dot(n);
+                if penetration > 0. {
+                    puck.location += n * (1.001 * penetration);
+                }
+            }
+            None => {
+                puck.velocity += (puck.velocity * Puck::DRAG + game.gravity) * dt;
+                puck.location += puck.velocity * dt;
+            }
+        }
+
+        puck.spin(puck.angular_velocity.dot(puck.up()) * dt);
+
+        puck.angular_velocity *= (Puck::W_MAX / puck.angular_velocity.length()).min(1.);
+        puck.velocity *= (Puck::V_MAX / puck.velocity.length()).min(1.);
+        puck.time += dt;
+
+        contact
+    }
+
+    pub fn get_puck_prediction_struct(game: &mut Game) -> PuckPrediction {
+        Puck::get_puck_prediction_struct_for_slices(game, Puck::STANDARD_NUM_SLICES)
+    }
+
+    // Returns an empty prediction if the game doesn't have a puck
+    pub fn get_puck_prediction_struct_for_slices(game: &mut Game, num_slices: usize) -> PuckPrediction {
+        let mut slices = Vec::with_capacity(num_slices);
+
+        if game.puck.is_some() {
+            for _ in 0..num_slices {
+                Puck::step(game, Puck::SIMULATION_DT);
+                slices.extend(game.puck);
+            }
+        }
+
+        PuckPrediction {
+            num_slices: slices.len(),
+            slices,
+        }
+    }
+}
+
+fn from_columns(a: Vec3, b: Vec3, c: Vec3) -> Mat3 {
+    Mat3 {
+        m: [[a.x, b.x, c.x], [a.y, b.y, c.y], [a.z, b.z, c.z]],
+    }
+}
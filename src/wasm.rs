@@ -0,0 +1,127 @@
+// WebAssembly bindings, enabled by the `wasm` feature and built with wasm-bindgen, see the README
+// Predictions are returned as Float32Arrays, vectors are interleaved as x, y, z for each slice
+
+use crate::simulation::ball::Ball;
+use crate::simulation::bvh::Bvh;
+use crate::simulation::game::Game;
+use crate::{dropshot_game, hoops_game, make_heatseeker, soccar_game, throwback_game};
+use crate::{load_dropshot, load_heatseeker, load_hoops, load_soccar, load_soccar_throwback};
+use glam::Vec3;
+use wasm_bindgen::prelude::*;
+
+fn from_slice(name: &str, v: &[f32]) -> Result<Vec3, JsError> {
+    match v {
+        &[x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err(JsError::new(&format!("{name} has {} elements, it should have 3", v.len()))),
+    }
+}
+
+#[wasm_bindgen(js_name = BallPrediction)]
+pub struct WasmBallPrediction {
+    slices: Vec<Ball>,
+}
+
+impl WasmBallPrediction {
+    fn vectors(&self, f: impl Fn(&Ball) -> Vec3) -> Vec<f32> {
+        self.slices.iter().map(f).flat_map(|v| [v.x, v.y, v.z]).collect()
+    }
+}
+
+#[wasm_bindgen(js_class = BallPrediction)]
+impl WasmBallPrediction {
+    #[wasm_bindgen(getter, js_name = numSlices)]
+    pub fn num_slices(&self) -> usize {
+        self.slices.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn time(&self) -> Vec<f32> {
+        self.slices.iter().map(|ball| ball.time).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn location(&self) -> Vec<f32> {
+        self.vectors(|ball| ball.location)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn velocity(&self) -> Vec<f32> {
+        self.vectors(|ball| ball.velocity)
+    }
+
+    #[wasm_bindgen(getter, js_name = angularVelocity)]
+    pub fn angular_velocity(&self) -> Vec<f32> {
+        self.vectors(|ball| ball.angular_velocity)
+    }
+}
+
+#[wasm_bindgen(js_name = Game)]
+pub struct WasmGame(Game);
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    // One of "soccar", "heatseeker", "hoops", "dropshot" or "throwback"
+    #[wasm_bindgen(js_name = loadArena)]
+    pub fn load_arena(arena: &str) -> Result<WasmGame, JsError> {
+        let game = match arena {
+            "soccar" => load_soccar(),
+            "heatseeker" => load_heatseeker(),
+            "hoops" => load_hoops(),
+            "dropshot" => load_dropshot(),
+            "throwback" => load_soccar_throwback(),
+            _ => return Err(JsError::new(&format!("Unknown arena: {arena}"))),
+        };
+
+        Ok(WasmGame(game))
+    }
+
+    // Like loadArena, but the collision mesh is read from the bytes of one of the trees in assets/prebuilt
+    // This lets a page fetch the trees instead of building the wasm with the prebuilt-bvh feature, which embeds all of them
+    #[wasm_bindgen(js_name = fromBvh)]
+    pub fn from_bvh(arena: &str, bvh: &[u8]) -> Result<WasmGame, JsError> {
+        let collision_mesh = Bvh::from_bin(bvh).map_err(|error| JsError::new(&error.to_string()))?;
+
+        let game = match arena {
+            "soccar" => soccar_game(collision_mesh),
+            "heatseeker" => {
+                let mut game = soccar_game(collision_mesh);
+                make_heatseeker(&mut game);
+                game
+            }
+            "hoops" => hoops_game(collision_mesh),
+            "dropshot" => dropshot_game(collision_mesh),
+            "throwback" => throwback_game(collision_mesh),
+            _ => return Err(JsError::new(&format!("Unknown arena: {arena}"))),
+        };
+
+        Ok(WasmGame(game))
+    }
+
+    // The ball's time, location, velocity and angular velocity, as a Float32Array of 10 elements
+    #[wasm_bindgen(getter)]
+    pub fn ball(&self) -> Vec<f32> {
+        let ball = &self.0.ball;
+
+        [ball.time].into_iter().chain([ball.location, ball.velocity, ball.angular_velocity].into_iter().flat_map(|v| [v.x, v.y, v.z])).collect()
+    }
+
+    // The vectors are arrays (or Float32Arrays) of 3 elements
+    #[wasm_bindgen(js_name = setBall)]
+    pub fn set_ball(&mut self, time: f32, location: &[f32], velocity: &[f32], angular_velocity: &[f32]) -> Result<(), JsError> {
+        self.0.ball.update(time, from_slice("location", location)?, from_slice("velocity", velocity)?, from_slice("angularVelocity", angular_velocity)?);
+
+        Ok(())
+    }
+
+    // Predicts `seconds` of the ball's path from its current state, 120 slices a second
+    // The ball's own state is left as it was
+    pub fn predict(&mut self, seconds: f32) -> WasmBallPrediction {
+        let start = self.0.ball;
+        let slices = Ball::get_ball_prediction_struct_for_time(&mut self.0, &seconds).slices;
+        self.0.ball = start;
+
+        WasmBallPrediction {
+            slices,
+        }
+    }
+}
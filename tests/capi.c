@@ -0,0 +1,59 @@
+// Exercises the C ABI, see the README for how to build and run it
+#include <assert.h>
+#include <math.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "rl_ball_sym.h"
+
+static void test_predict(RlbsGame *game) {
+    RlbsVector3 location = {0.f, 0.f, 1000.f};
+    RlbsVector3 velocity = {500.f, 1500.f, 0.f};
+    RlbsVector3 angular_velocity = {0.f, 0.f, 0.f};
+    rlbs_set_ball(game, 5.f, location, velocity, angular_velocity);
+
+    RlbsPredictionSlice ball = rlbs_get_ball(game);
+    assert(ball.physics.location.z == 1000.f);
+    assert(ball.game_seconds == 5.f);
+
+    // 2 seconds into the caller's buffer
+    RlbsPredictionSlice slices[240];
+    assert(rlbs_predict(game, slices, 240, 1) == 240);
+    assert(fabsf(slices[239].game_seconds - 7.f) < 0.001f);
+    assert(slices[239].physics.location.z < 1000.f);
+
+    // the ball is put back where it was
+    assert(rlbs_get_ball(game).physics.location.z == 1000.f);
+
+    // RLBot's layout, every other step
+    RlbsRLBotBallPrediction *prediction = malloc(sizeof(RlbsRLBotBallPrediction));
+    assert(rlbs_rlbot_max_slices() == RLBS_RLBOT_MAX_SLICES);
+    assert(sizeof(RlbsRLBotBallPrediction) == 52 * RLBS_RLBOT_MAX_SLICES + 4);
+
+    rlbs_predict_rlbot(game, prediction);
+    assert(prediction->num_slices == RLBS_RLBOT_MAX_SLICES);
+    assert(prediction->slices[119].game_seconds == slices[239].game_seconds);
+    assert(prediction->slices[119].physics.location.x == slices[239].physics.location.x);
+    assert(fabsf(prediction->slices[RLBS_RLBOT_MAX_SLICES - 1].game_seconds - 11.f) < 0.001f);
+
+    free(prediction);
+}
+
+int main(void) {
+    RlbsGame *games[] = {rlbs_load_soccar(), rlbs_load_heatseeker(), rlbs_load_hoops(), rlbs_load_dropshot()};
+
+    for (size_t i = 0; i < sizeof(games) / sizeof(games[0]); i++) {
+        assert(games[i] != NULL);
+        test_predict(games[i]);
+        rlbs_free_game(games[i]);
+    }
+
+    // null pointers are ignored
+    RlbsPredictionSlice slice;
+    assert(rlbs_predict(NULL, &slice, 1, 1) == 0);
+    assert(rlbs_get_ball(NULL).game_seconds == 0.f);
+    rlbs_free_game(NULL);
+
+    printf("capi: all tests passed\n");
+    return 0;
+}
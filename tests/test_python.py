@@ -0,0 +1,60 @@
+# Exercises the Python module, see the README for how to build it and run this with pytest
+import numpy as np
+import pytest
+import rl_ball_sym
+
+
+def test_predict():
+    game = rl_ball_sym.load_soccar()
+    game.set_ball(5.0, [0.0, 0.0, 1000.0], [500.0, 1500.0, 0.0])
+
+    prediction = game.predict(2.0)
+    assert prediction.num_slices == 240
+    assert prediction.time.shape == (240,)
+    assert prediction.location.shape == (240, 3)
+    assert prediction.velocity.shape == (240, 3)
+    assert prediction.angular_velocity.shape == (240, 3)
+    assert prediction.location.dtype == np.float32
+
+    assert abs(prediction.time[-1] - 7.0) < 0.001
+    assert np.all(np.diff(prediction.time) > 0)
+
+    # the ball is left where it was
+    assert game.ball.location == [0.0, 0.0, 1000.0]
+    assert game.ball.time == 5.0
+
+
+@pytest.mark.parametrize("load", [rl_ball_sym.load_soccar, rl_ball_sym.load_heatseeker, rl_ball_sym.load_hoops, rl_ball_sym.load_dropshot])
+def test_predict_batch(load):
+    game = load()
+
+    starts = 8
+    time = np.arange(starts, dtype=np.float32)
+    location = np.tile(np.array([0.0, 0.0, 1000.0], dtype=np.float32), (starts, 1))
+    velocity = np.stack([np.linspace(-2000.0, 2000.0, starts), np.zeros(starts), np.zeros(starts)], axis=1).astype(np.float32)
+    angular_velocity = np.zeros((starts, 3), dtype=np.float32)
+
+    batch = game.predict_batch(time, location, velocity, angular_velocity, 1.0)
+    assert len(batch) == starts
+    assert batch.num_slices == 120
+    assert batch.time.shape == (starts, 120)
+    assert batch.location.shape == (starts, 120, 3)
+
+    # every row is the same as predicting from that start state on its own
+    for i in range(starts):
+        game.set_ball(float(time[i]), location[i].tolist(), velocity[i].tolist())
+        prediction = game.predict(1.0)
+        np.testing.assert_array_equal(batch.location[i], prediction.location)
+        np.testing.assert_array_equal(batch.time[i], prediction.time)
+
+
+def test_predict_batch_shapes():
+    game = rl_ball_sym.load_soccar()
+    zeros = np.zeros((4, 3), dtype=np.float32)
+
+    try:
+        game.predict_batch(np.zeros(4, dtype=np.float32), zeros, np.zeros((3, 3), dtype=np.float32), zeros)
+    except ValueError as error:
+        assert "velocity" in str(error)
+    else:
+        assert False, "mismatched shapes should raise a ValueError"
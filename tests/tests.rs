@@ -1,10 +1,23 @@
+use glam::Vec3;
 use rand::Rng;
-use rl_ball_sym::simulation::ball::Ball;
-use rl_ball_sym::simulation::game::Game;
-use rl_ball_sym::simulation::geometry::Aabb;
+use rl_ball_sym::error::MeshError;
+use rl_ball_sym::linear_algebra::mat::Mat3;
+use rl_ball_sym::simulation::ball::{Ball, BallSolver};
+use rl_ball_sym::simulation::bullet;
+use rl_ball_sym::simulation::bvh::{Bvh, BvhBuilder, BvhNodeKind};
+use rl_ball_sym::simulation::car::Car;
+use rl_ball_sym::simulation::dropshot::{ChargeLevel, DropshotBall, TileState};
+use rl_ball_sym::simulation::game::{Arena, Game};
+use rl_ball_sym::simulation::geometry::{Aabb, Ray, Sphere, SurfaceKind, Tri};
+use rl_ball_sym::simulation::goal::{Goal, GoalShape, BLUE_TEAM, ORANGE_TEAM};
+use rl_ball_sym::simulation::heatseeker::Heatseeker;
+use rl_ball_sym::simulation::mesh::Mesh;
 use rl_ball_sym::simulation::morton::Morton;
-use rl_ball_sym::{load_dropshot, load_hoops, load_soccar, load_soccar_throwback};
-use vvec3::Vec3;
+use rl_ball_sym::simulation::mutators::{BallBounciness, BallSize, BallType, Gravity, Mutators};
+use rl_ball_sym::simulation::prediction::{FixedBallPrediction, RLBOT_MAX_SLICES};
+use rl_ball_sym::simulation::puck::Puck;
+use rl_ball_sym::{load_custom, load_dropshot, load_heatseeker, load_hoops, load_snowday, load_soccar, load_soccar_throwback};
+use std::sync::Arc;
 
 static mut GAME_0: Option<Game> = None;
 
@@ -74,9 +87,9 @@ fn gamemode_soccar() {
     assert_eq!(game.gravity.y as i64, 0);
     assert_eq!(game.gravity.z as i64, -650);
 
-    dbg!(game.collision_mesh.root.box_);
+    dbg!(game.arena.collision_mesh.nodes[0].box_);
 
-    assert_eq!(game.collision_mesh.num_leaves, 8028u64);
+    assert_eq!(game.arena.collision_mesh.num_leaves, 8028u64);
 
     assert_eq!(game.ball.time as i64, 0);
     assert_eq!(game.ball.location.x as i64, 0);
@@ -102,9 +115,9 @@ fn gamemode_hoops() {
     assert_eq!(game.gravity.y as i64, 0);
     assert_eq!(game.gravity.z as i64, -650);
 
-    dbg!(game.collision_mesh.root.box_);
+    dbg!(game.arena.collision_mesh.nodes[0].box_);
 
-    assert_eq!(game.collision_mesh.num_leaves, 15732u64);
+    assert_eq!(game.arena.collision_mesh.num_leaves, 15732u64);
 
     assert_eq!(game.ball.time as i64, 0);
     assert_eq!(game.ball.location.x as i64, 0);
@@ -130,9 +143,9 @@ fn gamemode_dropshot() {
     assert_eq!(game.gravity.y as i64, 0);
     assert_eq!(game.gravity.z as i64, -650);
 
-    dbg!(game.collision_mesh.root.box_);
+    dbg!(game.arena.collision_mesh.nodes[0].box_);
 
-    assert_eq!(game.collision_mesh.num_leaves, 3616u64);
+    assert_eq!(game.arena.collision_mesh.num_leaves, 3616u64);
 
     assert_eq!(game.ball.time as i64, 0);
     assert_eq!(game.ball.location.x as i64, 0);
@@ -158,11 +171,17 @@ fn gamemode_throwback_soccar() {
     assert_eq!(game.gravity.y as i64, 0);
     assert_eq!(game.gravity.z as i64, -650);
 
-    dbg!(&game.collision_mesh.root.box_);
-    dbg!(&game.collision_mesh.root.left.as_deref().unwrap().box_);
-    dbg!(&game.collision_mesh.root.right.as_deref().unwrap().box_);
+    dbg!(&game.arena.collision_mesh.nodes[0].box_);
+    if let BvhNodeKind::Branch {
+        right,
+        left,
+    } = game.arena.collision_mesh.nodes[0].kind
+    {
+        dbg!(&game.arena.collision_mesh.nodes[left as usize].box_);
+        dbg!(&game.arena.collision_mesh.nodes[right as usize].box_);
+    }
 
-    assert_eq!(game.collision_mesh.num_leaves, 9272);
+    assert_eq!(game.arena.collision_mesh.num_leaves, 9272);
 
     assert_eq!(game.ball.time as i64, 0);
     assert_eq!(game.ball.location.x as i64, 0);
@@ -210,7 +229,7 @@ fn basic_predict() {
     let mut y_locs = Vec::with_capacity(num_slices);
     let mut z_locs = Vec::with_capacity(num_slices);
 
-    dbg!(game.collision_mesh.global_box);
+    dbg!(game.arena.collision_mesh.global_box);
 
     for _ in 0..iters {
         game.ball.update(0., Vec3::new(rng.gen_range(-3900.0..3900.), rng.gen_range(-5000.0..5000.), rng.gen_range(100.0..1900.)), Vec3::new(rng.gen_range(-2000.0..2000.), rng.gen_range(-2000.0..2000.), rng.gen_range(-2000.0..2000.)), Vec3::new(rng.gen_range(-3.0..3.), rng.gen_range(-3.0..3.), rng.gen_range(-3.0..3.)));
@@ -237,14 +256,14 @@ fn basic_predict() {
     dbg!(*z_locs.iter().min().unwrap());
     dbg!(*z_locs.iter().max().unwrap());
 
-    assert!(*z_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.z as isize);
-    assert!(*z_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.z as isize);
+    assert!(*z_locs.iter().min().unwrap() > game.arena.collision_mesh.global_box.min.z as isize);
+    assert!(*z_locs.iter().max().unwrap() < game.arena.collision_mesh.global_box.max.z as isize);
 
-    assert!(*y_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.y as isize);
-    assert!(*y_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.y as isize);
+    assert!(*y_locs.iter().min().unwrap() > game.arena.collision_mesh.global_box.min.y as isize);
+    assert!(*y_locs.iter().max().unwrap() < game.arena.collision_mesh.global_box.max.y as isize);
 
-    assert!(*x_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.x as isize);
-    assert!(*x_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.x as isize);
+    assert!(*x_locs.iter().min().unwrap() > game.arena.collision_mesh.global_box.min.x as isize);
+    assert!(*x_locs.iter().max().unwrap() < game.arena.collision_mesh.global_box.max.x as isize);
 }
 
 #[test]
@@ -272,6 +291,25 @@ fn predict_hoops() {
     assert_eq!(ball_prediction.num_slices, 720);
 }
 
+#[test]
+fn hoops_back_walls() {
+    let mut game = load_hoops();
+
+    // nothing is in the way of a ball dropped in the middle of the arena
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::default(), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &2.);
+    let lowest = ball_prediction.slices.iter().map(|slice| slice.location.z).fold(f32::MAX, f32::min);
+    assert!(lowest < game.ball.radius + 5.);
+
+    // the back walls close the gap above the hoop, at the ends of the arena
+    game.ball.update(0., Vec3::new(0., 2500., 900.), Vec3::new(0., 2000., 0.), Vec3::default());
+    game.gravity = Vec3::default();
+    let ball_prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &1.);
+    let furthest = ball_prediction.slices.iter().map(|slice| slice.location.y).fold(f32::MIN, f32::max);
+    assert!(furthest < 3581.);
+    assert!(ball_prediction.slices.last().unwrap().velocity.y < 0.);
+}
+
 #[test]
 fn predict_dropshot() {
     let mut game = load_dropshot();
@@ -308,3 +346,1126 @@ fn check_for_nans() {
         assert!(slice.angular_velocity.z.is_finite());
     }
 }
+
+#[test]
+fn soccar_goal_event() {
+    let mut game = load_soccar();
+
+    game.ball.update(0., Vec3::new(0., 4000., 300.), Vec3::new(0., 2000., 0.), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    assert!(!ball_prediction.goals.is_empty());
+    let goal = ball_prediction.goals[0];
+    assert_eq!(goal.team, ORANGE_TEAM);
+    assert!((goal.time - ball_prediction.slices[goal.slice].time).abs() < f32::EPSILON);
+
+    // the location is where the ball crossed the goal line, between the goal's slice and the one before it
+    assert!((goal.location.y - (5120. + game.ball.radius)).abs() < 0.1);
+    assert!(ball_prediction.slices[goal.slice - 1].location.y < goal.location.y);
+    assert!(ball_prediction.slices[goal.slice].location.y > goal.location.y);
+
+    game.ball.update(0., Vec3::new(0., -4000., 300.), Vec3::new(0., -2000., 0.), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    assert_eq!(ball_prediction.goals[0].team, BLUE_TEAM);
+}
+
+#[test]
+fn soccar_no_goal_event() {
+    let mut game = load_soccar();
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    assert!(ball_prediction.goals.is_empty());
+}
+
+#[test]
+fn hoops_goal_event() {
+    let mut game = load_hoops();
+
+    // drop the ball through the orange rim, just off of the center line
+    game.ball.update(0., Vec3::new(200., 2982., 700.), Vec3::default(), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    assert!(!ball_prediction.goals.is_empty());
+    assert_eq!(ball_prediction.goals[0].team, ORANGE_TEAM);
+
+    // the ball's center drops through the rim's height
+    let goal = ball_prediction.goals[0];
+    assert!((goal.location.z - 310.28).abs() < 0.1);
+    assert!((goal.location.x - 200.).abs() < 1.);
+}
+
+#[test]
+fn hoops_goal_from_the_side() {
+    let game = load_hoops();
+    let GoalShape::Rim {
+        center,
+        radius,
+    } = game.arena.goals[1].shape
+    else {
+        panic!("hoops goals are rims");
+    };
+
+    let ball_at = |y: f32, z: f32| {
+        let mut ball = game.ball;
+        ball.location = Vec3::new(0., y, z);
+        ball
+    };
+
+    // moving in under the rim from the side isn't a goal
+    let previous = ball_at(center.y - radius - 20., center.z - 100.);
+    let slices: Vec<Ball> = (1..10).map(|i| ball_at(center.y - radius - 20. + i as f32 * 10., center.z - 100.)).collect();
+    assert!(Goal::find_events(&game.arena.goals, &previous, &slices, 0).is_empty());
+
+    // but dropping through it is
+    let previous = ball_at(center.y, center.z + 20.);
+    let slices: Vec<Ball> = (1..10).map(|i| ball_at(center.y, center.z + 20. - i as f32 * 10.)).collect();
+    let events = Goal::find_events(&game.arena.goals, &previous, &slices, 0);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].team, ORANGE_TEAM);
+}
+
+#[test]
+fn goals_match_meshes() {
+    // the hoops goals are the insides of the rims, only the halves of the rims in front of the back walls are in the mesh
+    let game = load_hoops();
+    for goal in &game.arena.goals {
+        let GoalShape::Rim {
+            center,
+            radius,
+        } = goal.shape
+        else {
+            panic!("hoops goals are rims");
+        };
+
+        let name = if goal.team == BLUE_TEAM {
+            "blue_rim"
+        } else {
+            "orange_rim"
+        };
+        let vertices: Vec<Vec3> = game.arena.collision_mesh.primitives.iter().filter(|tri| &*tri.surface.name == name).flat_map(|tri| tri.p).collect();
+        let distance = |p: &Vec3| (p.x - center.x).hypot(p.y - center.y);
+
+        // none of the rim is inside of the goal, and the inside of the rim touches it at the front and at the sides
+        assert!(vertices.iter().all(|p| distance(p) > radius - 1.));
+        assert!(vertices.iter().filter(|p| p.x.abs() < 50.).map(distance).fold(f32::MAX, f32::min) < radius + 1.);
+        assert!(vertices.iter().filter(|p| (p.y - center.y).abs() < 50.).map(distance).fold(f32::MAX, f32::min) < radius + 1.);
+        assert!((vertices.iter().map(|p| p.z).fold(f32::MAX, f32::min) - center.z).abs() < 0.01);
+    }
+
+    // the throwback goals are the openings between the fronts of the posts and under the crossbars
+    let game = load_soccar_throwback();
+    for goal in &game.arena.goals {
+        let GoalShape::Plane {
+            y,
+            half_width,
+            height,
+        } = goal.shape
+        else {
+            panic!("throwback goals are planes");
+        };
+
+        let name = if goal.team == BLUE_TEAM {
+            "blue_goal"
+        } else {
+            "orange_goal"
+        };
+        // both goals as if they were the orange one, only the goal mouth
+        let mouth: Vec<Vec3> = game.arena.collision_mesh.primitives.iter().filter(|tri| &*tri.surface.name == name).flat_map(|tri| tri.p).map(|p| Vec3::new(p.x.abs(), p.y * y.signum(), p.z)).filter(|p| p.y < y.abs() + 100.).collect();
+        let posts: Vec<&Vec3> = mouth.iter().filter(|p| p.z > 150. && p.z < 500. && p.x > 900. && p.x < 1010.).collect();
+
+        assert!((posts.iter().map(|p| p.x).fold(f32::MAX, f32::min) - half_width).abs() < 0.5);
+        assert!((posts.iter().map(|p| p.y).fold(f32::MAX, f32::min) - y.abs()).abs() < 0.5);
+        assert!((mouth.iter().filter(|p| p.x < 700. && p.z > 600.).map(|p| p.z).fold(f32::MAX, f32::min) - height).abs() < 0.5);
+    }
+}
+
+#[test]
+fn heatseeker_untouched() {
+    let mut game = load_soccar();
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(1000., 500., 0.), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    let mut heatseeker_game = load_heatseeker();
+    heatseeker_game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(1000., 500., 0.), Vec3::default());
+    let heatseeker_prediction = Ball::get_ball_prediction_struct(&mut heatseeker_game);
+
+    // the ball doesn't seek anything until it's been touched
+    for (slice, heatseeker_slice) in ball_prediction.slices.iter().zip(heatseeker_prediction.slices.iter()) {
+        assert!((slice.location - heatseeker_slice.location).length() < f32::EPSILON);
+    }
+}
+
+#[test]
+fn heatseeker_seeking() {
+    let mut game = load_heatseeker();
+
+    // heading towards the side wall, but seeking the orange goal
+    game.ball.update(0., Vec3::new(-2000., 0., 300.), Vec3::new(1500., 0., 0.), Vec3::default());
+    game.ball.heatseeker = Some(Heatseeker {
+        y_target_dir: 1.,
+        ..Default::default()
+    });
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    // the ball turns towards the orange goal and is sped up to the target speed
+    let slice = ball_prediction.slices[120];
+    assert!(slice.location.y > 1000.);
+    assert!(slice.velocity.y > 0.);
+    assert!((slice.velocity.length() - 2900.).abs() < 50.);
+
+    // bouncing off of the orange back wall sends the ball towards the blue goal
+    let bounce = ball_prediction.slices.iter().position(|slice| slice.heatseeker.unwrap().y_target_dir < 0.).unwrap();
+    assert!(ball_prediction.slices[bounce].location.y > 4800.);
+    assert!(ball_prediction.slices.last().unwrap().velocity.y < 0.);
+}
+
+#[test]
+fn heatseeker_max_speed() {
+    let mut game = load_heatseeker();
+    game.gravity = Vec3::default();
+
+    // every touch after the first speeds the ball up, until it's past the soccar ball's speed cap
+    let mut max_speed: f32 = 0.;
+    for _ in 0..25 {
+        game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(0., 2000., 0.), Vec3::default());
+        game.ball.heatseeker.as_mut().unwrap().on_touch(BLUE_TEAM);
+
+        for _ in 0..30 {
+            Ball::step(&mut game, 1. / 120.);
+            max_speed = max_speed.max(game.ball.velocity.length());
+        }
+    }
+
+    assert!(max_speed > 4500.);
+    assert!(max_speed <= Heatseeker::MAX_SPEED + 1.);
+}
+
+#[test]
+fn snowday_sliding_puck() {
+    let mut game = load_snowday();
+    let puck = game.puck.unwrap();
+
+    game.puck.as_mut().unwrap().update(0., puck.location, Vec3::new(300., 1000., 0.), Vec3::default(), Mat3::eye());
+
+    let puck_prediction = Puck::get_puck_prediction_struct_for_slices(&mut game, 120);
+    assert_eq!(puck_prediction.num_slices, 120);
+
+    // the puck slides along the ice without leaving it or tipping over
+    for slice in &puck_prediction.slices {
+        assert!(slice.location.z < puck.half_height + 5.);
+        assert!(slice.location.z > puck.half_height - 5.);
+        assert!(slice.up().z > 0.999);
+    }
+
+    let last = puck_prediction.slices.last().unwrap();
+    assert!(last.location.y > 800.);
+    assert!(last.velocity.y > 800.);
+}
+
+#[test]
+fn snowday_puck_lands_flat() {
+    let mut game = load_snowday();
+
+    // tilted and spinning around a horizontal axis as it falls
+    let (s, c) = 0.5_f32.sin_cos();
+    let orientation = Mat3 {
+        m: [[1., 0., 0.], [0., c, -s], [0., s, c]],
+    };
+    game.puck.as_mut().unwrap().update(0., Vec3::new(1000., 1000., 500.), Vec3::new(0., 0., -500.), Vec3::new(3., 0., 2.), orientation);
+
+    let puck_prediction = Puck::get_puck_prediction_struct(&mut game);
+
+    let last = puck_prediction.slices.last().unwrap();
+    assert!(last.up().z > 0.999);
+    assert!(last.location.z < last.half_height + 5.);
+
+    // it only spins around its axis once it's landed
+    assert!(last.angular_velocity.cross(last.up()).length() < 0.001);
+}
+
+#[test]
+fn snowday_puck_wall_bounce() {
+    let mut game = load_snowday();
+    let puck = game.puck.unwrap();
+
+    game.puck.as_mut().unwrap().update(0., Vec3::new(3000., 0., puck.half_height), Vec3::new(2000., 0., 0.), Vec3::default(), Mat3::eye());
+
+    let puck_prediction = Puck::get_puck_prediction_struct_for_slices(&mut game, 120);
+
+    let last = puck_prediction.slices.last().unwrap();
+    assert!(last.velocity.x < 0.);
+    assert!(last.location.x < 4096. - puck.radius);
+
+    // the soccar ball isn't affected by the puck
+    let mut soccar = load_soccar();
+    assert!(soccar.puck.is_none());
+    assert_eq!(Puck::get_puck_prediction_struct(&mut soccar).num_slices, 0);
+}
+
+#[test]
+fn dropshot_tile_damage() {
+    let mut game = load_dropshot();
+
+    // nobody has touched the ball, so it doesn't damage anything
+    game.ball.update(0., Vec3::new(0., 2000., 500.), Vec3::default(), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    assert!(ball_prediction.tile_damage.is_empty());
+
+    // a charged ball that blue touched last, dropped on orange's side
+    game.ball.update(0., Vec3::new(0., 2000., 500.), Vec3::default(), Vec3::default());
+    game.ball.dropshot = Some(DropshotBall {
+        charge: ChargeLevel::Charged,
+        team: Some(BLUE_TEAM),
+        ..Default::default()
+    });
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    // only the first bounce does damage
+    assert_eq!(ball_prediction.tile_damage.len(), 1);
+    let damage = &ball_prediction.tile_damage[0];
+    assert_eq!(damage.damaged.len(), 7);
+    assert!(damage.broken.is_empty());
+    assert!(ball_prediction.slices[damage.slice].dropshot.unwrap().has_damaged);
+
+    let tiles = game.dropshot_tiles.as_ref().unwrap();
+    let hit = tiles.tile_at(damage.location).unwrap();
+    assert!(damage.damaged.contains(&hit));
+    assert_eq!(tiles.tiles[hit].team, ORANGE_TEAM);
+
+    // the prediction doesn't change the game's tiles
+    assert!(tiles.tiles.iter().all(|tile| tile.state == TileState::Intact));
+
+    // a damaged tile breaks when it's hit again
+    game.dropshot_tiles.as_mut().unwrap().tiles[hit].state = TileState::Damaged;
+    game.ball.update(0., Vec3::new(0., 2000., 500.), Vec3::default(), Vec3::default());
+    game.ball.dropshot = Some(DropshotBall {
+        team: Some(BLUE_TEAM),
+        ..Default::default()
+    });
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    assert_eq!(ball_prediction.tile_damage[0].broken, vec![hit]);
+}
+
+#[test]
+fn dropshot_floor_quad() {
+    let game = load_dropshot();
+    let primitives = &game.arena.collision_mesh.primitives;
+
+    // open tiles only drop the floor quad, not the bottom of the walls around the tiles
+    assert!(primitives.iter().filter(|tri| tri.surface.kind == SurfaceKind::Floor).all(|tri| &*tri.surface.name == "floor"));
+    assert!(primitives.iter().any(|tri| &*tri.surface.name == "dropshot" && tri.surface.kind == SurfaceKind::Ramp && tri.unit_normal().z >= 0.9));
+}
+
+#[test]
+fn dropshot_open_tiles() {
+    let mut game = load_dropshot();
+    let p = Vec3::new(0., 2000., 500.);
+    let hit = game.dropshot_tiles.as_ref().unwrap().tile_at(p).unwrap();
+
+    // damaged tiles still hold the ball up
+    let mut states = vec![TileState::Intact; game.dropshot_tiles.as_ref().unwrap().tiles.len()];
+    states[hit] = TileState::Damaged;
+    game.dropshot_tiles.as_mut().unwrap().set_states(&states);
+
+    game.ball.update(0., p, Vec3::default(), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    assert!(ball_prediction.goals.is_empty());
+    assert!(ball_prediction.slices.last().unwrap().location.z > 0.);
+
+    // the ball falls through an open tile and scores on that tile's team
+    states[hit] = TileState::Open;
+    game.dropshot_tiles.as_mut().unwrap().set_states(&states);
+
+    game.ball.update(0., p, Vec3::default(), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    assert_eq!(ball_prediction.goals.len(), 1);
+    assert_eq!(ball_prediction.goals[0].team, ORANGE_TEAM);
+    assert!((ball_prediction.goals[0].location.z + game.ball.radius).abs() < 0.1);
+
+    // a tile that the ball breaks during the prediction is a hole for the rest of it
+    states[hit] = TileState::Damaged;
+    game.dropshot_tiles.as_mut().unwrap().set_states(&states);
+
+    game.ball.update(0., p, Vec3::default(), Vec3::default());
+    game.ball.dropshot = Some(DropshotBall {
+        team: Some(BLUE_TEAM),
+        ..Default::default()
+    });
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    assert_eq!(ball_prediction.tile_damage[0].broken, vec![hit]);
+    assert_eq!(ball_prediction.goals.len(), 1);
+    assert!(ball_prediction.goals[0].slice > ball_prediction.tile_damage[0].slice);
+    assert_eq!(game.dropshot_tiles.as_ref().unwrap().tiles[hit].state, TileState::Damaged);
+}
+
+#[test]
+fn sah_bvh() {
+    let mut game = load_soccar_throwback();
+    // the loaded tree is already an SAH tree with the prebuilt-bvh feature
+    Arc::make_mut(&mut game.arena).collision_mesh = game.arena.collision_mesh.rebuild(BvhBuilder::Morton);
+    let sah = game.arena.collision_mesh.rebuild(BvhBuilder::Sah);
+
+    assert_eq!(sah.num_leaves, game.arena.collision_mesh.num_leaves);
+    assert!(sah.expected_node_visits() < game.arena.collision_mesh.expected_node_visits());
+
+    game.ball.update(0., Vec3::new(-2000., -3000., 500.), Vec3::new(1500., 2000., 500.), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct_for_slices(&mut game.clone(), 240);
+
+    // the same triangles are hit, just in a different order
+    Arc::make_mut(&mut game.arena).collision_mesh = sah;
+    let sah_prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 240);
+
+    for (slice, sah_slice) in ball_prediction.slices.iter().zip(sah_prediction.slices.iter()) {
+        assert!((slice.location - sah_slice.location).length() < 1.);
+    }
+}
+
+#[test]
+fn prebuilt_bvh() {
+    let arenas = [("soccar", load_soccar as fn() -> Game), ("hoops", load_hoops), ("dropshot", load_dropshot), ("throwback", load_soccar_throwback)];
+
+    // the shipped trees have to be rebuilt with examples/prebuild_bvh.rs when the meshes or the SAH builder change
+    for (name, load) in arenas {
+        let mut built = Vec::new();
+        load().arena.collision_mesh.rebuild(BvhBuilder::Sah).to_bin(&mut built).unwrap();
+
+        let path = format!("{}/assets/prebuilt/{}.bvh", env!("CARGO_MANIFEST_DIR"), name);
+        assert_eq!(std::fs::read(&path).unwrap(), built, "{} is out of date", path);
+
+        let prebuilt = Bvh::from_bin_file(&path).unwrap();
+        assert_eq!(prebuilt.num_leaves, load().arena.collision_mesh.num_leaves);
+    }
+}
+
+#[test]
+fn continuous_collision() {
+    let mut game = load_soccar();
+
+    // with a big enough dt, the ball goes straight through the side wall in one step
+    game.ball.update(0., Vec3::new(3500., 0., 500.), Vec3::new(4000., 0., 0.), Vec3::default());
+    assert!(Ball::step(&mut game, 0.5).is_none());
+    assert!(game.ball.location.x > 4096.);
+
+    game.continuous_collision = true;
+    game.ball.update(0., Vec3::new(3500., 0., 500.), Vec3::new(4000., 0., 0.), Vec3::default());
+    let contact = Ball::step(&mut game, 0.5).unwrap();
+    assert!(contact.normal.x < -0.9);
+    assert!(game.ball.velocity.x < 0.);
+    assert!(game.ball.location.x < 4096.);
+
+    // at the normal dt, the prediction barely changes
+    game.ball.update(0., Vec3::new(-2000., -3000., 500.), Vec3::new(1500., 2000., 500.), Vec3::default());
+    let swept_prediction = Ball::get_ball_prediction_struct(&mut game.clone());
+
+    game.continuous_collision = false;
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    for (slice, swept_slice) in ball_prediction.slices.iter().zip(swept_prediction.slices.iter()).take(240) {
+        assert!((slice.location - swept_slice.location).length() < 1.);
+    }
+}
+
+#[test]
+fn raycast() {
+    let mut rng = rand::thread_rng();
+
+    for game in [load_soccar(), load_hoops(), load_dropshot()] {
+        for _ in 0..200 {
+            let ray = Ray {
+                start: Vec3::new(rng.gen_range(-4000.0..4000.), rng.gen_range(-5000.0..5000.), rng.gen_range(20.0..1900.)),
+                direction: Vec3::new(rng.gen_range(-8000.0..8000.), rng.gen_range(-8000.0..8000.), rng.gen_range(-3000.0..3000.)),
+            };
+
+            // the tree finds the same closest hit as checking every triangle
+            let closest = game.arena.collision_mesh.primitives.iter().filter_map(|tri| tri.raycast(&ray)).min_by(f32::total_cmp);
+            let hit = game.arena.collision_mesh.raycast(&ray);
+
+            assert_eq!(hit.is_some(), closest.is_some());
+            assert_eq!(game.arena.collision_mesh.raycast_any(&ray), closest.is_some());
+
+            if let (Some(hit), Some(t)) = (hit, closest) {
+                assert!((hit.distance - t * ray.direction.length()).abs() < 0.01);
+                assert!(hit.normal.dot(ray.direction) <= 0.);
+            }
+        }
+    }
+}
+
+#[test]
+fn closest_point() {
+    let mut rng = rand::thread_rng();
+
+    for game in [load_soccar(), load_hoops(), load_dropshot()] {
+        for _ in 0..100 {
+            let p = Vec3::new(rng.gen_range(-4500.0..4500.), rng.gen_range(-6000.0..6000.), rng.gen_range(-100.0..2200.));
+
+            // the tree finds the same distance as checking every triangle
+            let distance = game.arena.collision_mesh.primitives.iter().map(|tri| (tri.closest_point(p) - p).length()).min_by(f32::total_cmp).unwrap();
+            let closest = game.arena.collision_mesh.closest_point(p, f32::MAX).unwrap();
+
+            assert!((closest.distance - distance).abs() < 0.01);
+            assert!((closest.point - p).length() - distance < 0.01);
+
+            // and the same one when it only searches a little further than it needs to
+            let closest = game.arena.collision_mesh.closest_point(p, distance + 1.).unwrap();
+            assert!((closest.distance - distance).abs() < 0.01);
+            assert!(game.arena.collision_mesh.closest_point(p, distance - 1.).is_none());
+        }
+    }
+
+    // the middle of the field is inside of the arena, the area under the floor isn't
+    let game = load_soccar();
+    assert!((game.arena.collision_mesh.signed_distance(Vec3::new(0., 0., 100.), 500.) - 100.).abs() < 0.01);
+    assert!(game.arena.collision_mesh.signed_distance(Vec3::new(0., 0., -50.), 500.) < 0.);
+    assert_eq!(game.arena.collision_mesh.signed_distance(Vec3::new(0., 0., 1000.), 500.), 500.);
+}
+
+#[test]
+fn car_hitbox_queries() {
+    let game = load_soccar();
+
+    // an octane sinking 2 units into the floor in the middle of the field
+    let mut car = Car::default();
+    car.location.z = car.hitbox.height / 2. - car.hitbox.offset.z - 2.;
+
+    let manifold = game.arena.collision_mesh.collide_obb(&car.hitbox());
+    assert!(manifold.len() >= 4);
+    assert!(manifold.iter().all(|contact| (contact.depth - 2.).abs() < 0.01 && contact.normal.z > 0.99 && contact.surface.kind == SurfaceKind::Floor));
+
+    // driving up the side wall
+    car.location = Vec3::new(4096. - car.hitbox.height / 2. + car.hitbox.offset.z + 2., 0., 500.);
+    car.orientation = Mat3 {
+        m: [[0., 0., -1.], [1., 0., 0.], [0., -1., 0.]],
+    };
+    assert!((car.up().x + 1.).abs() < 0.0001);
+
+    let tris = game.arena.collision_mesh.intersect_obb(&car.hitbox());
+    assert!(!tris.is_empty());
+    assert!(tris.iter().all(|tri| tri.unit_normal().x < -0.99));
+
+    // the ball only touches the car when it's within its collision radius of the hitbox
+    let ball = Sphere {
+        center: car.hitbox().center + car.up() * 100.,
+        radius: game.ball.collision_radius,
+    };
+    assert!(car.hitbox().intersect_sphere(&ball));
+    assert!(!car.hitbox().intersect_sphere(&Sphere {
+        center: ball.center + car.up() * 50.,
+        ..ball
+    }));
+}
+
+// A floor and a wall that meet at x = 0
+fn corner_game() -> Game {
+    let (floor_0, floor_1) = (Vec3::new(0., -1000., 0.), Vec3::new(2000., -1000., 0.));
+    let (floor_2, floor_3) = (Vec3::new(2000., 1000., 0.), Vec3::new(0., 1000., 0.));
+    let (wall_0, wall_1) = (Vec3::new(0., -1000., 2000.), Vec3::new(0., 1000., 2000.));
+
+    let tris: Vec<Tri> = [[floor_0, floor_1, floor_2], [floor_0, floor_2, floor_3], [floor_0, floor_3, wall_1], [floor_0, wall_1, wall_0]]
+        .iter()
+        .map(|&p| Tri {
+            p,
+            ..Default::default()
+        })
+        .collect();
+
+    Game {
+        gravity: Vec3::new(0., 0., -650.),
+        arena: Arc::new(Arena {
+            collision_mesh: Bvh::from(&tris),
+            goals: Vec::new(),
+        }),
+        ball: Ball::initialize_soccar(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn multi_contact_corner_bounce() {
+    let mut game = corner_game();
+    assert!(game.arena.collision_mesh.primitives.iter().all(|tri| tri.unit_normal().x >= 0. && tri.unit_normal().z >= 0.));
+
+    // there's no recorded in-game data for this yet, so the bounce is checked against what a rigid wall and floor would do:
+    // each surface sends back its own part of the velocity, scaled by the restitution
+    let r = game.ball.collision_radius;
+    game.ball.update(0., Vec3::new(r - 1., 0., r - 1.), Vec3::new(-1000., 0., -200.), Vec3::default());
+
+    game.ball_solver = BallSolver::MultiContact;
+    Ball::step(&mut game, 1. / 120.).unwrap();
+
+    assert!(game.ball.velocity.x > 400. && game.ball.velocity.x < 650.);
+    assert!(game.ball.velocity.z > 0. && game.ball.velocity.z < 200.);
+
+    // the ball ends up out of both surfaces
+    assert!(game.ball.location.x >= r && game.ball.location.z >= r);
+}
+
+#[test]
+fn multi_contact_prediction() {
+    let mut game = load_soccar();
+    game.ball_solver = BallSolver::MultiContact;
+
+    // rolling fast into the side wall, through the ramp between the floor and the wall
+    game.ball.update(0., Vec3::new(0., 0., game.ball.collision_radius), Vec3::new(3000., 500., 0.), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    let contacts: usize = ball_prediction.slices.iter().filter(|slice| game.arena.collision_mesh.collide_manifold(&slice.hitbox()).len() > 1).count();
+    assert!(contacts > 0);
+
+    // the ball never goes through the arena's surface
+    for slice in &ball_prediction.slices {
+        assert!(game.arena.collision_mesh.signed_distance(slice.location, 200.) > 0.);
+    }
+
+    // and it isn't very different from the averaged contact when it's only touching one surface
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(500., 500., 0.), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct_for_slices(&mut game.clone(), 120);
+    game.ball_solver = BallSolver::SingleContact;
+    let averaged_prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 120);
+
+    for (slice, averaged_slice) in ball_prediction.slices.iter().zip(averaged_prediction.slices.iter()) {
+        assert!((slice.location - averaged_slice.location).length() < 1.);
+    }
+}
+
+#[test]
+fn bullet_bounce() {
+    let mut game = corner_game();
+    game.ball_solver = BallSolver::Bullet;
+    game.record_contacts = true;
+
+    // there's no recorded in-game data for this yet, so the ball is checked against what Bullet's solver should do:
+    // dropped onto the floor, it bounces with the ball's and the arena's restitution multiplied, until the bounces are too slow and then it stays at rest
+    let r = game.ball.collision_radius;
+    game.ball.update(0., Vec3::new(1000., 0., 500.), Vec3::default(), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &6.);
+    let contacts = ball_prediction.contacts.unwrap();
+
+    let bounce = &contacts[0];
+    assert!(bounce.normal.z > 0.99);
+    assert!(bounce.velocity_before.z < -500.);
+    assert!((bounce.velocity_after.z / -bounce.velocity_before.z - game.ball_physics.restitution * bullet::ARENA_RESTITUTION).abs() < 0.05);
+
+    let last = ball_prediction.slices.last().unwrap();
+    assert!((last.location.z - r).abs() < 1.);
+    assert!(last.velocity.length() < 1.);
+
+    // contacts are only found just before the ball touches them, so a fast ball sinks into the floor for up to a step
+    for slice in &ball_prediction.slices {
+        assert!(slice.location.z > r - bounce.velocity_before.z.abs() / 120.);
+    }
+}
+
+#[test]
+fn bullet_rolling() {
+    let mut game = corner_game();
+    game.ball_solver = BallSolver::Bullet;
+
+    // a ball that's sliding without spinning starts rolling because of the floor's friction
+    let r = game.ball.collision_radius;
+    game.ball.update(0., Vec3::new(200., 0., r), Vec3::new(400., 0., 0.), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &1.);
+    let last = ball_prediction.slices.last().unwrap();
+
+    // while it's sliding, the friction is the ball's and the arena's multiplied
+    let sliding = ball_prediction.slices[59];
+    assert!((sliding.velocity.x - (400. - bullet::BALL_FRICTION * bullet::ARENA_FRICTION * 650. * 0.5)).abs() < 10.);
+
+    assert!(last.velocity.x > 200. && last.velocity.x < 400.);
+    assert!((last.velocity.x - last.angular_velocity.y * r).abs() < 1.);
+    assert!((last.location.z - r).abs() < 1.);
+}
+
+#[test]
+fn bullet_prediction() {
+    let mut game = load_soccar();
+    game.ball_solver = BallSolver::Bullet;
+
+    // rolling fast into the side wall, through the ramp between the floor and the wall
+    game.ball.update(0., Vec3::new(0., 0., game.ball.collision_radius), Vec3::new(3000., 500., 0.), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    // the ball never goes through the arena's surface
+    for slice in &ball_prediction.slices {
+        assert!(game.arena.collision_mesh.signed_distance(slice.location, 200.) > 0.);
+    }
+
+    // and a ball in the air flies the same as with the other solvers, apart from how the drag is integrated
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(500., 500., 0.), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct_for_slices(&mut game.clone(), 60);
+    game.ball_solver = BallSolver::SingleContact;
+    let averaged_prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 60);
+
+    for (slice, averaged_slice) in ball_prediction.slices.iter().zip(averaged_prediction.slices.iter()) {
+        assert!((slice.location - averaged_slice.location).length() < 1.);
+    }
+}
+
+#[test]
+fn fixed_ball_prediction() {
+    let mut game = load_soccar();
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(500., 1500., 0.), Vec3::new(1., 0., 0.));
+
+    let mut fixed = Box::new(FixedBallPrediction::<RLBOT_MAX_SLICES>::default());
+    assert!(fixed.slices().is_empty());
+
+    fixed.fill(&mut game);
+    let ball_prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, RLBOT_MAX_SLICES);
+
+    assert_eq!(fixed.slices().len(), RLBOT_MAX_SLICES);
+    for (slice, ball) in fixed.slices().iter().zip(ball_prediction.slices.iter()) {
+        assert_eq!(Vec3::from(slice.physics.location), ball.location);
+        assert_eq!(Vec3::from(slice.physics.velocity), ball.velocity);
+        assert_eq!(Vec3::from(slice.physics.angular_velocity), ball.angular_velocity);
+        assert_eq!(slice.game_seconds, ball.time);
+    }
+
+    // refilling in place with RLBot's spacing
+    game.ball.update(10., Vec3::new(0., 0., 100.), Vec3::new(0., 0., 2000.), Vec3::default());
+    fixed.fill_every(&mut game, 2);
+
+    let ball_prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, RLBOT_MAX_SLICES * 2);
+    for (slice, ball) in fixed.slices().iter().zip(ball_prediction.slices.iter().skip(1).step_by(2)) {
+        assert_eq!(Vec3::from(slice.physics.location), ball.location);
+        assert_eq!(slice.game_seconds, ball.time);
+    }
+
+    assert!((fixed.slices().last().unwrap().game_seconds - 16.).abs() < 0.001);
+}
+
+#[test]
+fn fixed_ball_prediction_dropshot() {
+    let mut game = load_dropshot();
+    let p = Vec3::new(0., 2000., 500.);
+    let hit = game.dropshot_tiles.as_ref().unwrap().tile_at(p).unwrap();
+
+    // the charged ball breaks a damaged tile and falls through it
+    let mut states = vec![TileState::Intact; game.dropshot_tiles.as_ref().unwrap().tiles.len()];
+    states[hit] = TileState::Damaged;
+    game.dropshot_tiles.as_mut().unwrap().set_states(&states);
+
+    game.ball.update(0., p, Vec3::default(), Vec3::default());
+    game.ball.dropshot = Some(DropshotBall {
+        team: Some(BLUE_TEAM),
+        ..Default::default()
+    });
+
+    let mut fixed = Box::new(FixedBallPrediction::<720>::default());
+    fixed.fill(&mut game);
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    assert_eq!(ball_prediction.tile_damage[0].broken, vec![hit]);
+    assert!(fixed.slices().last().unwrap().physics.location.z < 0.);
+
+    for (slice, ball) in fixed.slices().iter().zip(ball_prediction.slices.iter()) {
+        assert_eq!(Vec3::from(slice.physics.location), ball.location);
+        assert_eq!(Vec3::from(slice.physics.velocity), ball.velocity);
+        assert_eq!(slice.game_seconds, ball.time);
+    }
+
+    // the game's tiles are left as they were
+    assert_eq!(game.dropshot_tiles.as_ref().unwrap().tiles[hit].state, TileState::Damaged);
+}
+
+#[test]
+fn shared_arena() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Game>();
+
+    let game = load_soccar();
+    let starts: Vec<Vec3> = (0..4).map(|i| Vec3::new(i as f32 * 500. - 1000., 1000., 500.)).collect();
+
+    // every thread predicts from its own ball against the same collision mesh
+    let predictions: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = starts
+            .iter()
+            .map(|&location| {
+                let mut thread_game = game.clone();
+                assert!(Arc::ptr_eq(&thread_game.arena, &game.arena));
+
+                scope.spawn(move || {
+                    thread_game.ball.update(0., location, Vec3::new(0., 2000., 1000.), Vec3::default());
+                    Ball::get_ball_prediction_struct(&mut thread_game)
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+
+    assert_eq!(Arc::strong_count(&game.arena), 1);
+
+    for (&location, prediction) in starts.iter().zip(predictions.iter()) {
+        let mut game = game.clone();
+        game.ball.update(0., location, Vec3::new(0., 2000., 1000.), Vec3::default());
+        let expected = Ball::get_ball_prediction_struct(&mut game);
+
+        assert_eq!(prediction.goals.len(), expected.goals.len());
+        for (slice, expected_slice) in prediction.slices.iter().zip(expected.slices.iter()) {
+            assert_eq!(slice.location, expected_slice.location);
+        }
+    }
+
+    // changing a clone's arena copies it and leaves the others alone
+    let mut changed = game.clone();
+    Arc::make_mut(&mut changed.arena).goals.clear();
+    assert!(!Arc::ptr_eq(&game.arena, &changed.arena));
+    assert_eq!(game.arena.goals.len(), 2);
+}
+
+#[test]
+fn record_contacts() {
+    let mut game = load_soccar();
+
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(0., 0., 0.), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    assert!(ball_prediction.contacts.is_none());
+
+    game.record_contacts = true;
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(0., 0., 0.), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    let contacts = ball_prediction.contacts.unwrap();
+
+    // the first bounce is on the floor
+    let bounce = &contacts[0];
+    assert_eq!(bounce.surface.kind, SurfaceKind::Floor);
+    assert_eq!(&*bounce.surface.name, "floor");
+    assert!(bounce.velocity_before.z < 0.);
+    assert!(bounce.velocity_after.z > 0.);
+    assert!(bounce.location.z.abs() < 1.);
+    assert!(bounce.normal.z > 0.99);
+
+    // the ball is thrown straight at the ceiling
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(0., 0., 2000.), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    let contacts = ball_prediction.contacts.unwrap();
+
+    assert_eq!(contacts[0].surface.kind, SurfaceKind::Ceiling);
+
+    // the ball is thrown at the side wall
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(3000., 0., 0.), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    let contacts = ball_prediction.contacts.unwrap();
+
+    assert_eq!(contacts[0].surface.kind, SurfaceKind::Wall);
+    assert_eq!(&*contacts[0].surface.name, "side_wall");
+}
+
+#[test]
+fn surface_tags() {
+    let game = load_soccar();
+
+    // resting on the floor in the middle of the field
+    let contact = game.arena.collision_mesh.collide(&Sphere {
+        center: Vec3::new(0., 0., 90.),
+        radius: 93.15,
+    });
+    assert_eq!(&*contact.unwrap().surface.name, "floor");
+
+    // inside the back of the orange goal
+    let contact = game.arena.collision_mesh.collide(&Sphere {
+        center: Vec3::new(0., 5950., 300.),
+        radius: 93.15,
+    });
+    let surface = contact.unwrap().surface;
+    assert_eq!(&*surface.name, "orange_goal");
+    assert_eq!(surface.kind, SurfaceKind::Goal);
+
+    let game = load_hoops();
+
+    // sitting on the blue rim
+    let contact = game.arena.collision_mesh.collide(&Sphere {
+        center: Vec3::new(705., -2982.31, 420.),
+        radius: 93.15,
+    });
+    let surface = contact.unwrap().surface;
+    assert_eq!(&*surface.name, "blue_rim");
+    assert_eq!(surface.kind, SurfaceKind::Rim);
+}
+
+// a 2000x2000x1000 box around the origin, with the floor at z = 0
+const BOX_OBJ: &str = "
+# box
+v -1000 -1000 0
+v 1000 -1000 0
+v 1000 1000 0
+v -1000 1000 0
+v -1000 -1000 1000
+v 1000 -1000 1000
+v 1000 1000 1000
+v -1000 1000 1000
+f 1 2 3 4
+f 8 7 6 5
+f 1 5 6 2
+f 2/1 6/1 7/1 3/1
+f 3//1 7//1 8//1 4//1
+f -8 -5 -1 -4
+";
+
+#[test]
+fn custom_obj_arena() {
+    let mesh = Mesh::from_obj(BOX_OBJ.as_bytes()).unwrap();
+
+    assert_eq!(mesh.vertices.len(), 8 * 3);
+    assert_eq!(mesh.ids.len(), 12 * 3);
+
+    let ball = Ball::initialize_custom(91.25, 93.15);
+    let mut game = load_custom(&[mesh], ball, Vec3::new(0., 0., -650.)).unwrap();
+
+    assert_eq!(game.arena.collision_mesh.num_leaves, 12);
+
+    game.ball.update(0., Vec3::new(0., 0., 500.), Vec3::new(1500., 800., 0.), Vec3::default());
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    for slice in ball_prediction.slices {
+        assert!(slice.location.x.abs() < 1000.);
+        assert!(slice.location.y.abs() < 1000.);
+        assert!(slice.location.z > 0. && slice.location.z < 1000.);
+    }
+}
+
+#[test]
+fn custom_obj_names() {
+    let obj = BOX_OBJ.replace("f 1 2 3 4", "o box\ng floor\nf 1 2 3 4").replace("f 8 7 6 5", "g roof\nf 8 7 6 5").replace("f 1 5 6 2", "g side walls\nf 1 5 6 2");
+    let mesh = Mesh::from_obj(obj.as_bytes()).unwrap();
+
+    let names: Vec<&str> = mesh.surfaces.iter().map(|surface| &*surface.name).collect();
+    assert_eq!(names, ["floor", "floor", "roof", "roof", "side walls", "side walls", "side walls", "side walls", "side walls", "side walls", "side walls", "side walls"]);
+
+    let game = load_custom(&[mesh], Ball::initialize_custom(91.25, 93.15), Vec3::new(0., 0., -650.)).unwrap();
+
+    let floor = game.arena.collision_mesh.primitives.iter().find(|tri| &*tri.surface.name == "floor").unwrap();
+    assert_eq!(floor.surface.kind, SurfaceKind::Floor);
+
+    // without any groups the mesh is left untagged
+    assert!(Mesh::from_obj(BOX_OBJ.as_bytes()).unwrap().surfaces.is_empty());
+}
+
+#[test]
+fn custom_bin_arena() {
+    let corner = Mesh::from_bin_files("assets/soccar/soccar_corner_ids.bin", "assets/soccar/soccar_corner_vertices.bin").unwrap();
+
+    let game = load_custom(&[corner], Ball::initialize_custom(91.25, 93.15), Vec3::new(0., 0., -650.)).unwrap();
+
+    assert_eq!(game.arena.collision_mesh.num_leaves, 10560 / 12);
+
+    assert!(matches!(Mesh::from_bin_files("assets/soccar/not_a_mesh_ids.bin", "assets/soccar/not_a_mesh_vertices.bin"), Err(MeshError::Io(_))));
+}
+
+#[test]
+fn invalid_obj() {
+    assert!(matches!(
+        Mesh::from_obj("v 1 2\n".as_bytes()),
+        Err(MeshError::Parse {
+            line: 1,
+            ..
+        })
+    ));
+    assert!(matches!(
+        Mesh::from_obj("v 1 2 3\nv 4 5 6\nf 1 2\n".as_bytes()),
+        Err(MeshError::Parse {
+            line: 3,
+            ..
+        })
+    ));
+    assert!(matches!(
+        Mesh::from_obj("v 1 2 3\nf a b c\n".as_bytes()),
+        Err(MeshError::Parse {
+            line: 2,
+            ..
+        })
+    ));
+    assert!(matches!(
+        Mesh::from_obj("v 1 2 3\nv 4 5 6\nv 7 8 9\nf 1 2 4\n".as_bytes()),
+        Err(MeshError::IndexOutOfBounds {
+            triangle: 0,
+            index: 3,
+            num_vertices: 3
+        })
+    ));
+    assert!(matches!(
+        Mesh::from_obj("v 1 2 3\nv 4 5 6\nv 7 8 9\nf 1 2 3\n".as_bytes()),
+        Err(MeshError::DegenerateTriangle {
+            triangle: 0
+        })
+    ));
+    assert!(matches!(
+        Mesh::from_obj("v 1 2 3\nv 4 5 NaN\nv 7 8 9\nf 1 2 3\n".as_bytes()),
+        Err(MeshError::NanVertex {
+            vertex: 1
+        })
+    ));
+    assert!(matches!(Mesh::from_obj("# nothing here\n".as_bytes()), Err(MeshError::Empty)));
+}
+
+#[test]
+fn invalid_bin() {
+    let vertices: Vec<u8> = [0f32, 0., 0., 1., 0., 0., 0., 1., 0.].iter().flat_map(|v| v.to_le_bytes()).collect();
+    let ids: Vec<u8> = [0i32, 1, 2].iter().flat_map(|id| id.to_le_bytes()).collect();
+
+    assert!(Mesh::from_bin(&ids[..], &vertices[..]).is_ok());
+
+    // cut off in the middle of a number
+    assert!(matches!(
+        Mesh::from_bin(&ids[..], &vertices[..vertices.len() - 1]),
+        Err(MeshError::Truncated {
+            file: "vertices",
+            ..
+        })
+    ));
+    // cut off in the middle of a vertex
+    assert!(matches!(
+        Mesh::from_bin(&ids[..], &vertices[..vertices.len() - 4]),
+        Err(MeshError::Truncated {
+            file: "vertices",
+            ..
+        })
+    ));
+    // cut off in the middle of a triangle
+    assert!(matches!(
+        Mesh::from_bin(&ids[..ids.len() - 4], &vertices[..]),
+        Err(MeshError::Truncated {
+            file: "ids",
+            ..
+        })
+    ));
+
+    let ids: Vec<u8> = [0i32, 1, 3].iter().flat_map(|id| id.to_le_bytes()).collect();
+    assert!(matches!(
+        Mesh::from_bin(&ids[..], &vertices[..]),
+        Err(MeshError::IndexOutOfBounds {
+            index: 3,
+            ..
+        })
+    ));
+
+    assert!(matches!(load_custom(&[], Ball::default(), Vec3::default()), Err(MeshError::Empty)));
+
+    // a mesh that was built in memory has no file to be truncated, it's just missing part of a triangle
+    let incomplete = Mesh {
+        ids: vec![0, 1, 2, 0],
+        vertices: [0f32, 0., 0., 1., 0., 0., 0., 1., 0.].to_vec(),
+        surfaces: Vec::new(),
+    };
+    assert!(matches!(
+        load_custom(&[incomplete], Ball::default(), Vec3::default()),
+        Err(MeshError::IncompleteTriangle {
+            ids: 4,
+        })
+    ));
+}
+
+#[test]
+fn default_mutators() {
+    let mut game = load_soccar();
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(1000., 500., 0.), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    let mut mutated_game = load_soccar();
+    Mutators::default().apply(&mut mutated_game);
+    mutated_game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::new(1000., 500., 0.), Vec3::default());
+    let mutated_prediction = Ball::get_ball_prediction_struct(&mut mutated_game);
+
+    for (slice, mutated_slice) in ball_prediction.slices.iter().zip(mutated_prediction.slices.iter()) {
+        assert!((slice.location - mutated_slice.location).length() < f32::EPSILON);
+    }
+}
+
+#[test]
+fn gravity_and_bounciness_mutators() {
+    let mut game = load_soccar();
+    game.record_contacts = true;
+
+    let mutators = Mutators {
+        gravity: Gravity::Low,
+        ball_bounciness: BallBounciness::SuperHigh,
+        ball_size: BallSize::Large,
+        ..Default::default()
+    };
+    mutators.apply(&mut game);
+
+    assert_eq!(game.gravity.z as i64, -325);
+    assert_eq!(game.ball.radius as i64, 182);
+
+    game.ball.update(0., Vec3::new(0., 0., 1000.), Vec3::default(), Vec3::default());
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    // the ball bounces when its surface touches the floor, not its center
+    let bounce = ball_prediction.contacts.unwrap()[0].clone();
+    assert_eq!(bounce.surface.kind, SurfaceKind::Floor);
+    assert!((bounce.velocity_after.z / bounce.velocity_before.z + 0.9).abs() < 0.05);
+
+    let default_game = load_soccar();
+    assert!(bounce.time > ((1000. - default_game.ball.collision_radius) * 2. / 650_f32).sqrt());
+}
+
+#[test]
+fn mutators_apply_once() {
+    let mutators = Mutators {
+        ball_type: BallType::Basketball,
+        ball_size: BallSize::Large,
+        gravity: Gravity::High,
+        ..Default::default()
+    };
+
+    let mut once = load_soccar();
+    mutators.apply(&mut once);
+
+    let mut twice = load_soccar();
+    mutators.apply(&mut twice);
+    mutators.apply(&mut twice);
+
+    assert_eq!(once.ball.radius, twice.ball.radius);
+    assert_eq!(once.ball.collision_radius, twice.ball.collision_radius);
+    assert_eq!(once.ball.location.z, twice.ball.location.z);
+    assert_eq!(once.ball.moi, twice.ball.moi);
+    assert_eq!(once.gravity.z, twice.gravity.z);
+
+    // going back to the default mutators gives back the standard ball
+    Mutators::default().apply(&mut twice);
+    let soccar = load_soccar();
+    assert_eq!(twice.ball.radius, soccar.ball.radius);
+    assert_eq!(twice.ball.location.z, soccar.ball.location.z);
+}
+
+#[test]
+fn ball_type_mutators() {
+    let mut game = load_soccar();
+    Mutators {
+        ball_type: BallType::Basketball,
+        ball_size: BallSize::Medium,
+        ..Default::default()
+    }
+    .apply(&mut game);
+
+    let hoops = Ball::initialize_hoops();
+    assert!((game.ball.radius - hoops.radius * 1.5).abs() < 0.001);
+    assert!((game.ball.collision_radius - hoops.collision_radius * 1.5).abs() < 0.001);
+    assert!(game.puck.is_none());
+
+    // the puck is the same one that snow day is played with
+    let mut game = load_soccar();
+    Mutators {
+        ball_type: BallType::Puck,
+        ..Default::default()
+    }
+    .apply(&mut game);
+
+    let puck = game.puck.unwrap();
+    let snowday = load_snowday().puck.unwrap();
+    assert_eq!(puck.radius, snowday.radius);
+    assert_eq!(puck.half_height, snowday.half_height);
+}
@@ -0,0 +1,61 @@
+// Run with `cargo test --target wasm32-unknown-unknown --features wasm --test wasm`, see the README
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use rl_ball_sym::wasm::WasmGame;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn predict() {
+    let mut game = WasmGame::load_arena("soccar").unwrap();
+    game.set_ball(5., &[0., 0., 1000.], &[500., 1500., 0.], &[0., 0., 0.]).unwrap();
+
+    let prediction = game.predict(2.);
+    assert_eq!(prediction.num_slices(), 240);
+
+    let time = prediction.time();
+    let location = prediction.location();
+    assert_eq!(time.len(), 240);
+    assert_eq!(location.len(), 240 * 3);
+    assert_eq!(prediction.velocity().len(), 240 * 3);
+    assert_eq!(prediction.angular_velocity().len(), 240 * 3);
+
+    assert!((time[239] - 7.).abs() < 0.001);
+    assert!(location[239 * 3 + 2] < 1000.);
+
+    // the ball is left where it was
+    assert_eq!(game.ball(), vec![5., 0., 0., 1000., 500., 1500., 0., 0., 0., 0.]);
+}
+
+#[wasm_bindgen_test]
+fn load_arenas() {
+    for arena in ["soccar", "heatseeker", "hoops", "dropshot", "throwback"] {
+        let mut game = WasmGame::load_arena(arena).unwrap();
+        assert_eq!(game.predict(1.).num_slices(), 120);
+    }
+
+    assert!(WasmGame::load_arena("rumble").is_err());
+}
+
+#[wasm_bindgen_test]
+fn from_bvh() {
+    let bvh = include_bytes!("../assets/prebuilt/hoops.bvh");
+
+    let mut game = WasmGame::from_bvh("hoops", bvh).unwrap();
+    game.set_ball(0., &[0., 0., 1000.], &[0., 0., 0.], &[0., 0., 0.]).unwrap();
+    let location = game.predict(2.).location();
+
+    // the ball lands on the floor of the hoops arena
+    let lowest = location.iter().skip(2).step_by(3).fold(f32::MAX, |a, &b| a.min(b));
+    assert!(lowest > 80. && lowest < 100.);
+
+    assert!(WasmGame::from_bvh("rumble", bvh).is_err());
+    assert!(WasmGame::from_bvh("hoops", &bvh[..100]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn set_ball_lengths() {
+    let mut game = WasmGame::load_arena("soccar").unwrap();
+
+    assert!(game.set_ball(0., &[0., 0.], &[0., 0., 0.], &[0., 0., 0.]).is_err());
+    assert!(game.set_ball(0., &[0., 0., 100.], &[0., 0., 0., 0.], &[0., 0., 0.]).is_err());
+}